@@ -0,0 +1,350 @@
+//! IO-free grammar analysis.
+//!
+//! This module exposes the whole analysis pipeline (grammar parsing, FIRST/FOLLOW,
+//! parser classification and string parsing) as plain functions over owned data,
+//! so it can be driven from environments without stdin such as WebAssembly.
+
+use crate::earley::EarleyParser;
+use crate::error::Result;
+use crate::first_follow::{
+    compute_first_sets, compute_follow_sets, format_set, nullable_symbols, FirstSets, FollowSets,
+};
+use crate::grammar::{is_ll_k_candidate, Grammar};
+use crate::ll1::LL1Parser;
+use crate::slr1::SLR1Parser;
+use crate::symbol::Symbol;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Which deterministic parsers can be built for a grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Classification {
+    /// The grammar is both LL(1) and SLR(1)
+    Both,
+    /// The grammar is LL(1) but not SLR(1)
+    LL1Only,
+    /// The grammar is SLR(1) but not LL(1)
+    SLR1Only,
+    /// The grammar is neither LL(1) nor SLR(1)
+    Neither,
+}
+
+impl Classification {
+    /// Builds a classification from whether each parser could be built.
+    pub const fn from_support(is_ll1: bool, is_slr1: bool) -> Self {
+        match (is_ll1, is_slr1) {
+            (true, true) => Classification::Both,
+            (true, false) => Classification::LL1Only,
+            (false, true) => Classification::SLR1Only,
+            (false, false) => Classification::Neither,
+        }
+    }
+
+    /// Checks if the grammar is LL(1).
+    pub const fn is_ll1(&self) -> bool {
+        matches!(self, Classification::Both | Classification::LL1Only)
+    }
+
+    /// Checks if the grammar is SLR(1).
+    pub const fn is_slr1(&self) -> bool {
+        matches!(self, Classification::Both | Classification::SLR1Only)
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Both => write!(f, "LL(1) and SLR(1)"),
+            Classification::LL1Only => write!(f, "LL(1)"),
+            Classification::SLR1Only => write!(f, "SLR(1)"),
+            Classification::Neither => write!(f, "neither LL(1) nor SLR(1)"),
+        }
+    }
+}
+
+/// The verdict for a single input string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputVerdict {
+    /// The input string as given
+    pub input: String,
+    /// Whether the input was accepted, or `None` if no parser could be built
+    pub accepted: Option<bool>,
+}
+
+/// The owned result of analyzing a grammar and a batch of inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisResult {
+    /// Which deterministic parsers apply to the grammar
+    pub classification: Classification,
+    /// FIRST sets of the nonterminals as `(nonterminal, "{a, b}")`, sorted
+    pub first_sets: Vec<(String, String)>,
+    /// FOLLOW sets of the nonterminals as `(nonterminal, "{a, $}")`, sorted
+    pub follow_sets: Vec<(String, String)>,
+    /// One verdict per input, in the order given
+    pub verdicts: Vec<InputVerdict>,
+}
+
+/// Classifies a grammar by attempting to build both deterministic parsers.
+pub fn classify(
+    grammar: &Grammar,
+    first_sets: &FirstSets,
+    follow_sets: &FollowSets,
+) -> Classification {
+    let is_ll1 = LL1Parser::build(grammar.clone(), first_sets.clone(), follow_sets.clone()).is_ok();
+    let is_slr1 = SLR1Parser::build(grammar.clone(), follow_sets.clone()).is_ok();
+    Classification::from_support(is_ll1, is_slr1)
+}
+
+/// A grammar with its FIRST, FOLLOW and nullable sets and its
+/// classification, each computed once.
+///
+/// This is the entry point for analyzing a grammar and building its parsers
+/// without wiring the sets from one step to the next by hand.
+#[derive(Debug, Clone)]
+pub struct GrammarAnalysis {
+    grammar: Grammar,
+    first_sets: FirstSets,
+    follow_sets: FollowSets,
+    nullable: HashSet<Symbol>,
+    classification: Classification,
+}
+
+impl GrammarAnalysis {
+    /// Analyzes a grammar, computing every set and the classification.
+    pub fn new(grammar: Grammar) -> Self {
+        let first_sets = compute_first_sets(&grammar);
+        let follow_sets = compute_follow_sets(&grammar, &first_sets);
+        let nullable = nullable_symbols(&grammar);
+        let classification = classify(&grammar, &first_sets, &follow_sets);
+        Self {
+            grammar,
+            first_sets,
+            follow_sets,
+            nullable,
+            classification,
+        }
+    }
+
+    /// Returns the analyzed grammar.
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Returns the FIRST sets.
+    pub fn first_sets(&self) -> &FirstSets {
+        &self.first_sets
+    }
+
+    /// Returns the FOLLOW sets.
+    pub fn follow_sets(&self) -> &FollowSets {
+        &self.follow_sets
+    }
+
+    /// Returns the nonterminals that derive ε.
+    pub fn nullable(&self) -> &HashSet<Symbol> {
+        &self.nullable
+    }
+
+    /// Returns which deterministic parsers apply to the grammar.
+    pub fn classification(&self) -> Classification {
+        self.classification
+    }
+
+    /// Builds the LL(1) parser from the stored sets.
+    pub fn ll1_parser(&self) -> Result<LL1Parser> {
+        LL1Parser::build(
+            self.grammar.clone(),
+            self.first_sets.clone(),
+            self.follow_sets.clone(),
+        )
+    }
+
+    /// Builds the SLR(1) parser from the stored FOLLOW sets.
+    pub fn slr1_parser(&self) -> Result<SLR1Parser> {
+        SLR1Parser::build(self.grammar.clone(), self.follow_sets.clone())
+    }
+}
+
+/// Finds the groups of mutually left-recursive nonterminals.
+///
+/// # Algorithm
+/// Build the left-corner graph with an edge A → B whenever some production
+/// A → X₁...XₖBβ has X₁...Xₖ all nullable, so hidden left recursion through
+/// nullable prefixes (e.g. A → BAc with B → ε) is detected. Each returned
+/// group is a strongly connected component of that graph containing a cycle,
+/// sorted by symbol; the groups themselves are sorted too.
+pub fn left_recursion_cycles(grammar: &Grammar) -> Vec<Vec<Symbol>> {
+    let nullable = nullable_symbols(grammar);
+
+    // Left-corner edges
+    let mut edges: HashMap<Symbol, HashSet<Symbol>> = HashMap::new();
+    for production in grammar.all_productions() {
+        for symbol in &production.rhs {
+            if symbol.is_nonterminal() {
+                edges.entry(production.lhs).or_default().insert(*symbol);
+            }
+            if !nullable.contains(symbol) {
+                break;
+            }
+        }
+    }
+
+    // Nonterminals reachable through one or more left-corner edges
+    let reach = |from: Symbol| -> HashSet<Symbol> {
+        let mut seen = HashSet::new();
+        let mut worklist: Vec<Symbol> = edges.get(&from).into_iter().flatten().copied().collect();
+        while let Some(nt) = worklist.pop() {
+            if seen.insert(nt) {
+                worklist.extend(edges.get(&nt).into_iter().flatten().copied());
+            }
+        }
+        seen
+    };
+
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+    let reaches: HashMap<Symbol, HashSet<Symbol>> =
+        nonterminals.iter().map(|nt| (*nt, reach(*nt))).collect();
+
+    let mut cycles: Vec<Vec<Symbol>> = Vec::new();
+    for nt in &nonterminals {
+        if !reaches[nt].contains(nt) || cycles.iter().any(|c| c.contains(nt)) {
+            continue;
+        }
+        let cycle: Vec<Symbol> = nonterminals
+            .iter()
+            .filter(|other| reaches[nt].contains(other) && reaches[other].contains(nt))
+            .copied()
+            .collect();
+        cycles.push(cycle);
+    }
+
+    cycles
+}
+
+/// Returns the smallest lookahead k, up to `max_k`, for which the grammar is
+/// strong LL(k).
+///
+/// Each k from 1 to `max_k` is tried with [`is_ll_k_candidate`]. `None` means
+/// even `max_k` symbols do not separate the alternatives; left-recursive
+/// grammars never succeed for any k.
+pub fn min_lookahead(grammar: &Grammar, max_k: usize) -> Option<usize> {
+    (1..=max_k).find(|&k| is_ll_k_candidate(grammar, k))
+}
+
+/// Returns the shortest input of at most `max_len` terminals that exactly one
+/// of the grammars accepts, or `None` if they agree on every such input.
+///
+/// Inputs are tried shortest first and then in symbol order over the union of
+/// both terminal alphabets, so the search is exhaustive and its cost grows
+/// exponentially with `max_len`.
+pub fn shortest_difference(first: &Grammar, second: &Grammar, max_len: usize) -> Option<String> {
+    shortest_difference_from(first, second, 0, max_len)
+}
+
+/// Like [`shortest_difference`], but ignores inputs shorter than `min_len`.
+pub(crate) fn shortest_difference_from(
+    first: &Grammar,
+    second: &Grammar,
+    min_len: usize,
+    max_len: usize,
+) -> Option<String> {
+    let first_parser = EarleyParser::new(first.clone());
+    let second_parser = EarleyParser::new(second.clone());
+    // Inputs are read verbatim, so terminals are spelled by their characters
+    let mut terminals: Vec<char> = first
+        .terminals()
+        .union(second.terminals())
+        .filter_map(Symbol::as_char)
+        .collect();
+    terminals.sort();
+
+    let mut layer: Vec<String> = vec![String::new()];
+    for len in 0..=max_len {
+        if len >= min_len
+            && let Some(input) = layer
+                .iter()
+                .find(|input| first_parser.parse(input) != second_parser.parse(input))
+        {
+            return Some(input.clone());
+        }
+        if len == max_len {
+            break;
+        }
+        layer = layer
+            .iter()
+            .flat_map(|prefix| terminals.iter().map(move |t| format!("{}{}", prefix, t)))
+            .collect();
+    }
+
+    None
+}
+
+/// Checks whether two grammars accept the same inputs of at most `max_len`
+/// terminals.
+///
+/// Language equivalence is undecidable in general, so this is a bounded check:
+/// `true` only means no input up to that length tells the grammars apart. See
+/// [`shortest_difference`] for the distinguishing input.
+pub fn equivalent_up_to(first: &Grammar, second: &Grammar, max_len: usize) -> bool {
+    shortest_difference(first, second, max_len).is_none()
+}
+
+/// Analyzes a grammar given as text and parses each input with it.
+///
+/// The text uses the same format as the CLI (a count line followed by the
+/// production lines). Inputs are parsed with the LL(1) parser when available,
+/// otherwise with the SLR(1) parser; if neither applies every verdict is `None`.
+/// No IO is performed.
+pub fn analyze_text(grammar_text: &str, inputs: &[&str]) -> Result<AnalysisResult> {
+    let lines: Vec<String> = grammar_text.lines().map(String::from).collect();
+    let grammar = Grammar::parse(&lines)?;
+
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let ll1_result = LL1Parser::build(grammar.clone(), first_sets.clone(), follow_sets.clone());
+    let slr1_result = SLR1Parser::build(grammar.clone(), follow_sets.clone());
+    let classification = Classification::from_support(ll1_result.is_ok(), slr1_result.is_ok());
+
+    let verdicts = inputs
+        .iter()
+        .map(|input| {
+            let accepted = match (&ll1_result, &slr1_result) {
+                (Ok(parser), _) => Some(parser.parse(input)),
+                (Err(_), Ok(parser)) => Some(parser.parse(input)),
+                (Err(_), Err(_)) => None,
+            };
+            InputVerdict {
+                input: input.to_string(),
+                accepted,
+            }
+        })
+        .collect();
+
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+
+    Ok(AnalysisResult {
+        classification,
+        first_sets: nonterminals
+            .iter()
+            .map(|nt| {
+                (
+                    nt.to_string(),
+                    format_set(first_sets.first_of(*nt), grammar.config()),
+                )
+            })
+            .collect(),
+        follow_sets: nonterminals
+            .iter()
+            .map(|nt| {
+                (
+                    nt.to_string(),
+                    format_set(follow_sets.follow_of(*nt), grammar.config()),
+                )
+            })
+            .collect(),
+        verdicts,
+    })
+}
@@ -1,19 +1,306 @@
 //! CLI module for the grammar parser application.
 
-use crate::error::Result;
-use crate::first_follow::{compute_first_sets, compute_follow_sets};
+use crate::analysis::{shortest_difference, GrammarAnalysis};
+use crate::error::{GrammarError, Result};
+use crate::first_follow;
+use crate::first_follow::{
+    compute_first_sets, compute_follow_sets, format_first_sets, format_follow_sets,
+};
 use crate::grammar::Grammar;
 use crate::ll1::LL1Parser;
 use crate::slr1::SLR1Parser;
-use std::io::{self, BufRead, Write};
+use crate::trace::ParseTrace;
+use crate::transform::GrammarPipeline;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Command-line options.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// The subcommand to run.
+    pub command: Command,
+    /// Print the step-by-step parse trace after each verdict (`--trace`).
+    pub trace: bool,
+    /// Print why each rejected input was rejected (`--explain`).
+    pub explain: bool,
+}
+
+/// A subcommand, given as the first positional argument.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Command {
+    /// No subcommand: read the grammar and the strings from stdin.
+    #[default]
+    Run,
+    /// `analyze <file>`: print FIRST/FOLLOW sets and the parser classification.
+    Analyze(PathBuf),
+    /// `parse <file>`: read the grammar and the strings from a file.
+    Parse(PathBuf),
+    /// `convert <file> --to <target>`: print the transformed grammar.
+    Convert {
+        path: PathBuf,
+        target: ConvertTarget,
+    },
+    /// `export <file> --format <format>`: print the grammar in another format.
+    Export { path: PathBuf, format: ExportFormat },
+    /// `equiv <file1> <file2> --max-len <n>`: compare the languages of two
+    /// grammars on every input of at most `n` terminals.
+    Equiv { paths: [PathBuf; 2], max_len: usize },
+}
+
+/// The target of the `convert` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertTarget {
+    /// Chomsky normal form (`cnf`)
+    Cnf,
+    /// Greibach normal form (`gnf`)
+    Gnf,
+    /// Left recursion eliminated and left-factored (`ll1`)
+    LL1,
+}
+
+/// The format of the `export` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The LR(0) automaton in Graphviz DOT, conflicts highlighted (`dot`)
+    Dot,
+    /// The SLR(1) ACTION and GOTO tables (`csv`)
+    Csv,
+    /// The grammar and its FIRST/FOLLOW sets (`latex`)
+    Latex,
+}
+
+impl Options {
+    /// Parses the command-line arguments, excluding the program name.
+    pub fn from_args<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args: Vec<String> = args.into_iter().map(|a| a.as_ref().to_string()).collect();
+        let mut args = args.iter();
+        let mut options = Self::default();
+        let mut subcommand: Option<&str> = None;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let mut target = None;
+        let mut format = None;
+        let mut max_len = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--trace" => options.trace = true,
+                "--explain" => options.explain = true,
+                "--to" => target = Some(flag_value(&mut args, "--to")?),
+                "--format" => format = Some(flag_value(&mut args, "--format")?),
+                "--max-len" => max_len = Some(flag_value(&mut args, "--max-len")?),
+                name @ ("analyze" | "parse" | "convert" | "export" | "equiv")
+                    if subcommand.is_none() =>
+                {
+                    subcommand = Some(name);
+                }
+                other if subcommand.is_some() && !other.starts_with("--") => {
+                    paths.push(PathBuf::from(other));
+                }
+                other => {
+                    return Err(GrammarError::InvalidFormat(format!(
+                        "unknown argument '{}'",
+                        other
+                    )));
+                }
+            }
+        }
+
+        let file_limit = if subcommand == Some("equiv") { 2 } else { 1 };
+        if let Some(extra) = paths.get(file_limit) {
+            return Err(GrammarError::InvalidFormat(format!(
+                "unknown argument '{}'",
+                extra.display()
+            )));
+        }
+        if target.is_some() && subcommand != Some("convert") {
+            return Err(GrammarError::InvalidFormat(
+                "--to is only valid with convert".to_string(),
+            ));
+        }
+        if format.is_some() && subcommand != Some("export") {
+            return Err(GrammarError::InvalidFormat(
+                "--format is only valid with export".to_string(),
+            ));
+        }
+        if max_len.is_some() && subcommand != Some("equiv") {
+            return Err(GrammarError::InvalidFormat(
+                "--max-len is only valid with equiv".to_string(),
+            ));
+        }
+
+        let Some(name) = subcommand else {
+            return Ok(options);
+        };
+        if name == "equiv" {
+            let Ok(paths) = <[PathBuf; 2]>::try_from(paths) else {
+                return Err(GrammarError::InvalidFormat(
+                    "equiv needs two grammar files".to_string(),
+                ));
+            };
+            let max_len = match max_len.as_deref().map(str::parse) {
+                Some(Ok(max_len)) => max_len,
+                _ => return Err(invalid_choice("--max-len", max_len.as_deref(), "<n>")),
+            };
+            options.command = Command::Equiv { paths, max_len };
+            return Ok(options);
+        }
+        let path = paths.pop().ok_or_else(|| {
+            GrammarError::InvalidFormat(format!("missing grammar file for '{}'", name))
+        })?;
+        options.command = match name {
+            "analyze" => Command::Analyze(path),
+            "parse" => Command::Parse(path),
+            "convert" => Command::Convert {
+                path,
+                target: match target.as_deref() {
+                    Some("cnf") => ConvertTarget::Cnf,
+                    Some("gnf") => ConvertTarget::Gnf,
+                    Some("ll1") => ConvertTarget::LL1,
+                    other => return Err(invalid_choice("--to", other, "cnf|gnf|ll1")),
+                },
+            },
+            _ => Command::Export {
+                path,
+                format: match format.as_deref() {
+                    Some("dot") => ExportFormat::Dot,
+                    Some("csv") => ExportFormat::Csv,
+                    Some("latex") => ExportFormat::Latex,
+                    other => return Err(invalid_choice("--format", other, "dot|csv|latex")),
+                },
+            },
+        };
+        Ok(options)
+    }
+}
+
+/// Returns the value following a flag.
+fn flag_value<'a>(args: &mut impl Iterator<Item = &'a String>, flag: &str) -> Result<String> {
+    args.next()
+        .cloned()
+        .ok_or_else(|| GrammarError::InvalidFormat(format!("missing value for {}", flag)))
+}
+
+/// Builds the error for a missing or unknown flag value.
+fn invalid_choice(flag: &str, value: Option<&str>, choices: &str) -> GrammarError {
+    match value {
+        Some(value) => GrammarError::InvalidFormat(format!(
+            "invalid value '{}' for {}; expected {}",
+            value, flag, choices
+        )),
+        None => GrammarError::InvalidFormat(format!("{} {} is required", flag, choices)),
+    }
+}
 
 /// Main CLI runner for the grammar parser.
 pub fn run() -> Result<()> {
-    let stdin = io::stdin();
-    let mut lines = stdin.lock().lines();
+    run_with_options(&Options::default())
+}
+
+/// Runs the CLI with the given options.
+pub fn run_with_options(options: &Options) -> Result<()> {
+    match &options.command {
+        Command::Run => {
+            let stdin = io::stdin();
+            run_session(options, &mut stdin.lock().lines())
+        }
+        Command::Parse(path) => {
+            let file = File::open(path)?;
+            run_session(options, &mut BufReader::new(file).lines())
+        }
+        Command::Analyze(path) => {
+            print!("{}", report(&read_grammar(path)?));
+            Ok(())
+        }
+        Command::Convert { path, target } => {
+            let pipeline = GrammarPipeline::new(read_grammar(path)?);
+            let converted = match target {
+                ConvertTarget::Cnf => pipeline.to_cnf(),
+                ConvertTarget::Gnf => pipeline.to_gnf(),
+                ConvertTarget::LL1 => pipeline.eliminate_left_recursion().left_factor(),
+            }
+            .build()?;
+            print!("{}", converted.to_pretty_string());
+            Ok(())
+        }
+        Command::Export { path, format } => {
+            print!("{}", export(&read_grammar(path)?, *format)?);
+            Ok(())
+        }
+        Command::Equiv { paths, max_len } => {
+            let first = read_grammar(&paths[0])?;
+            let second = read_grammar(&paths[1])?;
+            match shortest_difference(&first, &second, *max_len) {
+                None => println!("Equivalent up to length {}.", max_len),
+                Some(input) => {
+                    let only_in = if first.accepts(&input)? {
+                        &paths[0]
+                    } else {
+                        &paths[1]
+                    };
+                    println!(
+                        "Not equivalent: \"{}\" is only accepted by {}.",
+                        input,
+                        only_in.display()
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Reads a grammar file: a count line followed by the production lines.
+fn read_grammar(path: &Path) -> Result<Grammar> {
+    Grammar::from_reader(BufReader::new(File::open(path)?))
+}
+
+/// Renders the `analyze` report: the grammar, its FIRST and FOLLOW sets and
+/// which deterministic parsers apply, with the LL(1) conflicts if any.
+fn report(grammar: &Grammar) -> String {
+    let analysis = GrammarAnalysis::new(grammar.clone());
+    let (first_sets, follow_sets) = (analysis.first_sets(), analysis.follow_sets());
+
+    let mut output = format!("Grammar:\n{}\n", grammar.to_pretty_string());
+    output.push_str(&format!("{}\n", format_first_sets(grammar, first_sets)));
+    output.push_str(&format!("{}\n", format_follow_sets(grammar, follow_sets)));
+    output.push_str(&format!("Grammar is {}.\n", analysis.classification()));
+    for conflict in LL1Parser::conflicts(grammar, first_sets, follow_sets) {
+        output.push_str(&format!("{}\n", conflict));
+    }
+    if let Some(explanation) = SLR1Parser::explain_conflict(grammar, follow_sets) {
+        output.push_str(&format!("{}\n", explanation));
+    }
+    output
+}
+
+/// Renders the `export` output for a grammar.
+///
+/// CSV needs the SLR(1) tables and fails when the grammar is not SLR(1); DOT
+/// draws the LR(0) automaton of any grammar.
+fn export(grammar: &Grammar, format: ExportFormat) -> Result<String> {
+    let first_sets = compute_first_sets(grammar);
+    let follow_sets = compute_follow_sets(grammar, &first_sets);
+    Ok(match format {
+        ExportFormat::Dot => SLR1Parser::to_dot_with_conflicts(grammar, &follow_sets),
+        ExportFormat::Csv => SLR1Parser::build(grammar.clone(), follow_sets)?.to_csv(),
+        ExportFormat::Latex => format!(
+            "{}\n{}",
+            grammar.to_latex(),
+            first_follow::to_latex(grammar, &first_sets, &follow_sets)
+        ),
+    })
+}
 
+/// Reads a grammar followed by the strings to parse, as in the default
+/// stdin mode.
+fn run_session<R: BufRead>(options: &Options, lines: &mut io::Lines<R>) -> Result<()> {
     // Read grammar
-    let grammar_lines = read_grammar(&mut lines)?;
+    let grammar_lines = Grammar::read_lines(lines)?;
     let grammar = Grammar::parse(&grammar_lines)?;
 
     // Compute FIRST and FOLLOW sets
@@ -30,17 +317,17 @@ pub fn run() -> Result<()> {
     match (ll1_result, slr1_result) {
         (Ok(ll1_parser), Ok(slr1_parser)) => {
             // Case 1: Both LL(1) and SLR(1)
-            interactive_mode(ll1_parser, slr1_parser, &mut lines)?;
+            interactive_mode(ll1_parser, slr1_parser, options, lines)?;
         }
         (Ok(ll1_parser), Err(_)) => {
             // Case 2: LL(1) only
             println!("Grammar is LL(1).");
-            parse_strings(|s| ll1_parser.parse(s), &mut lines)?;
+            parse_strings(&ll1_parser, options, lines)?;
         }
         (Err(_), Ok(slr1_parser)) => {
             // Case 3: SLR(1) only
             println!("Grammar is SLR(1).");
-            parse_strings(|s| slr1_parser.parse(s), &mut lines)?;
+            parse_strings(&slr1_parser, options, lines)?;
         }
         (Err(_), Err(_)) => {
             // Case 4: Neither LL(1) nor SLR(1)
@@ -51,42 +338,48 @@ pub fn run() -> Result<()> {
     Ok(())
 }
 
-/// Reads the grammar from input lines.
-///
-/// First line is the number n, then n production lines.
-fn read_grammar<R: BufRead>(lines: &mut io::Lines<R>) -> Result<Vec<String>> {
-    let mut grammar_lines = Vec::new();
-
-    // Read first line (number of nonterminals)
-    let n_str = lines.next().ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "Expected number of nonterminals",
-        )
-    })??;
-
-    let n = n_str
-        .trim()
-        .parse::<usize>()
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid number"))?;
-
-    grammar_lines.push(n_str);
-
-    // Read n production lines
-    for _ in 0..n {
-        let line = lines.next().ok_or_else(|| {
-            io::Error::new(io::ErrorKind::UnexpectedEof, "Expected production line")
-        })??;
-        grammar_lines.push(line);
+/// The parser operations the CLI needs, implemented by both parsers.
+trait CliParser {
+    fn parse(&self, input: &str) -> bool;
+    fn parse_trace(&self, input: &str) -> ParseTrace;
+    fn parse_detailed(&self, input: &str) -> Result<()>;
+}
+
+impl CliParser for LL1Parser {
+    fn parse(&self, input: &str) -> bool {
+        LL1Parser::parse(self, input)
+    }
+
+    fn parse_trace(&self, input: &str) -> ParseTrace {
+        LL1Parser::parse_trace(self, input)
     }
 
-    Ok(grammar_lines)
+    fn parse_detailed(&self, input: &str) -> Result<()> {
+        LL1Parser::parse_detailed(self, input)
+    }
+}
+
+impl CliParser for SLR1Parser {
+    fn parse(&self, input: &str) -> bool {
+        SLR1Parser::parse(self, input)
+    }
+
+    fn parse_trace(&self, input: &str) -> ParseTrace {
+        SLR1Parser::parse_trace(self, input)
+    }
+
+    fn parse_detailed(&self, input: &str) -> Result<()> {
+        SLR1Parser::parse_detailed(self, input)
+    }
 }
 
 /// Parses strings until an empty line is encountered.
-fn parse_strings<F, R>(parse_fn: F, lines: &mut io::Lines<R>) -> Result<()>
+///
+/// With `--explain`, each `no` is followed by the rejection reason; with
+/// `--trace`, each verdict is followed by the parse trace.
+fn parse_strings<P, R>(parser: &P, options: &Options, lines: &mut io::Lines<R>) -> Result<()>
 where
-    F: Fn(&str) -> bool,
+    P: CliParser,
     R: BufRead,
 {
     while let Some(Ok(line)) = lines.next() {
@@ -95,22 +388,36 @@ where
             break;
         }
 
-        let result = parse_fn(trimmed);
+        let result = parser.parse(trimmed);
         println!("{}", if result { "yes" } else { "no" });
+        if options.explain
+            && !result
+            && let Err(e) = parser.parse_detailed(trimmed)
+        {
+            println!("{}", e);
+        }
+        if options.trace {
+            print!("{}", parser.parse_trace(trimmed));
+        }
     }
 
     Ok(())
 }
 
 /// Interactive mode for when grammar is both LL(1) and SLR(1).
+///
+/// Besides the parser choices, `:grammar` reads a new grammar (count line and
+/// productions) and switches to it. The new grammar must also be both LL(1)
+/// and SLR(1); otherwise the error is reported and the previous grammar kept.
 fn interactive_mode<R: BufRead>(
-    ll1_parser: LL1Parser,
-    slr1_parser: SLR1Parser,
+    mut ll1_parser: LL1Parser,
+    mut slr1_parser: SLR1Parser,
+    options: &Options,
     lines: &mut io::Lines<R>,
 ) -> Result<()> {
     loop {
         // Print prompt
-        print!("Select a parser (T: for LL(1), B: for SLR(1), Q: quit):\n");
+        println!("Select a parser (T: for LL(1), B: for SLR(1), Q: quit):");
         io::stdout().flush()?;
 
         // Read choice
@@ -123,11 +430,20 @@ fn interactive_mode<R: BufRead>(
         match choice.as_str() {
             "Q" | "q" => break,
             "T" | "t" => {
-                parse_strings(|s| ll1_parser.parse(s), lines)?;
+                parse_strings(&ll1_parser, options, lines)?;
             }
             "B" | "b" => {
-                parse_strings(|s| slr1_parser.parse(s), lines)?;
+                parse_strings(&slr1_parser, options, lines)?;
             }
+            ":grammar" => match reload_grammar(lines) {
+                Ok((ll1, slr1)) => {
+                    ll1_parser = ll1;
+                    slr1_parser = slr1;
+                    println!("Grammar reloaded.");
+                }
+                Err(GrammarError::Io(e)) => return Err(e.into()),
+                Err(e) => eprintln!("Error: {}; keeping the previous grammar", e),
+            },
             _ => {
                 // Invalid choice, re-prompt
                 continue;
@@ -137,3 +453,14 @@ fn interactive_mode<R: BufRead>(
 
     Ok(())
 }
+
+/// Reads a grammar from the input and builds both parsers for it.
+fn reload_grammar<R: BufRead>(lines: &mut io::Lines<R>) -> Result<(LL1Parser, SLR1Parser)> {
+    let grammar = Grammar::parse(&Grammar::read_lines(lines)?)?;
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let ll1_parser = LL1Parser::build(grammar.clone(), first_sets, follow_sets.clone())?;
+    let slr1_parser = SLR1Parser::build(grammar, follow_sets)?;
+    Ok((ll1_parser, slr1_parser))
+}
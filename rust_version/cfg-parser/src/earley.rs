@@ -0,0 +1,542 @@
+//! Earley parser implementation.
+//!
+//! This module implements Earley's general chart parsing algorithm, which handles
+//! any context-free grammar (including ambiguous and left-recursive ones), and
+//! extracts a shared packed parse forest (SPPF) for accepted inputs.
+
+use crate::first_follow::nullable_symbols;
+use crate::grammar::{Grammar, Production};
+use crate::symbol::Symbol;
+use crate::transform::productive_nonterminals;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+/// An Earley item: a production index, a dot position and the origin set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct EarleyItem {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+/// The Earley chart: one item set per input position.
+type Chart = Vec<Vec<EarleyItem>>;
+
+/// A node of the shared packed parse forest.
+///
+/// Each node covers the input span `start..end` for a symbol. Nonterminal nodes
+/// hold one packed family per distinct way of deriving that span; terminal
+/// leaves have no families. Child nodes are shared between families.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SppfNode {
+    /// The grammar symbol derived by this node
+    pub symbol: Symbol,
+    /// Start position in the input (inclusive)
+    pub start: usize,
+    /// End position in the input (exclusive)
+    pub end: usize,
+    /// Alternative derivations of this span
+    pub families: Vec<PackedNode>,
+}
+
+/// One derivation alternative of an [`SppfNode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedNode {
+    /// The production applied at this node
+    pub production: Production,
+    /// Child nodes, one per symbol of the production body
+    pub children: Vec<Rc<SppfNode>>,
+}
+
+/// A single parse tree extracted from a parse forest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseTree {
+    /// The symbol at this node
+    pub symbol: Symbol,
+    /// Children (empty for terminals and ε-productions)
+    pub children: Vec<ParseTree>,
+}
+
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.symbol.is_terminal() {
+            return write!(f, "{}", self.symbol);
+        }
+        write!(f, "{}(", self.symbol)?;
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", child)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl SppfNode {
+    /// Returns up to `limit` distinct parse trees represented by this node.
+    ///
+    /// The trees of each shared node are built once, so the cost is
+    /// polynomial in the size of the forest for a fixed `limit`.
+    pub fn trees(&self, limit: usize) -> impl Iterator<Item = ParseTree> {
+        let trees = self.collect_trees(limit, &mut HashMap::new());
+        Rc::try_unwrap(trees)
+            .unwrap_or_else(|shared| (*shared).clone())
+            .into_iter()
+    }
+
+    /// Counts the parse trees represented by this node, saturating at `max`.
+    ///
+    /// Counts are computed bottom-up with each shared node visited once,
+    /// so this stays cheap even when the number of trees is exponential.
+    pub fn count_trees(&self, max: usize) -> usize {
+        self.count(max, &mut HashMap::new())
+    }
+
+    fn count(&self, max: usize, memo: &mut HashMap<*const SppfNode, usize>) -> usize {
+        if self.families.is_empty() {
+            return 1.min(max);
+        }
+        let key: *const SppfNode = self;
+        if let Some(&count) = memo.get(&key) {
+            return count;
+        }
+
+        let mut total = 0usize;
+        for family in &self.families {
+            let mut product = 1usize;
+            for child in &family.children {
+                product = product.saturating_mul(child.count(max, memo)).min(max);
+            }
+            total = total.saturating_add(product).min(max);
+        }
+        memo.insert(key, total);
+        total
+    }
+
+    fn collect_trees(
+        &self,
+        limit: usize,
+        memo: &mut HashMap<*const SppfNode, Rc<Vec<ParseTree>>>,
+    ) -> Rc<Vec<ParseTree>> {
+        if self.families.is_empty() {
+            return Rc::new(vec![ParseTree {
+                symbol: self.symbol,
+                children: Vec::new(),
+            }]);
+        }
+        let key: *const SppfNode = self;
+        if let Some(trees) = memo.get(&key) {
+            return Rc::clone(trees);
+        }
+
+        let mut result = Vec::new();
+        'families: for family in &self.families {
+            // Cartesian product of the children's trees, bounded by `limit`
+            let mut partial: Vec<Vec<ParseTree>> = vec![Vec::new()];
+            for child in &family.children {
+                let child_trees = child.collect_trees(limit, memo);
+                let mut next = Vec::new();
+                'product: for prefix in &partial {
+                    for tree in child_trees.iter() {
+                        let mut extended = prefix.clone();
+                        extended.push(tree.clone());
+                        next.push(extended);
+                        if next.len() >= limit {
+                            break 'product;
+                        }
+                    }
+                }
+                partial = next;
+            }
+
+            for children in partial {
+                if result.len() >= limit {
+                    break 'families;
+                }
+                result.push(ParseTree {
+                    symbol: self.symbol,
+                    children,
+                });
+            }
+        }
+
+        let result = Rc::new(result);
+        memo.insert(key, Rc::clone(&result));
+        result
+    }
+}
+
+/// Earley parser for arbitrary context-free grammars.
+#[derive(Debug)]
+pub struct EarleyParser {
+    grammar: Grammar,
+    /// Nonterminals that can derive ε
+    nullable: HashSet<Symbol>,
+    /// Whether each production, by index, mentions only productive
+    /// nonterminals; the others can never complete and are not predicted
+    usable: Vec<bool>,
+}
+
+impl EarleyParser {
+    /// Creates an Earley parser for a grammar.
+    ///
+    /// Unlike the LL(1) and SLR(1) parsers this never fails: every
+    /// context-free grammar can be handled.
+    pub fn new(grammar: Grammar) -> Self {
+        let nullable = nullable_symbols(&grammar);
+        let productive = productive_nonterminals(&grammar);
+        let usable = grammar
+            .all_productions()
+            .iter()
+            .map(|p| {
+                p.rhs
+                    .iter()
+                    .all(|s| !s.is_nonterminal() || productive.contains(s))
+            })
+            .collect();
+        Self {
+            grammar,
+            nullable,
+            usable,
+        }
+    }
+
+    /// Returns the body of a production, treating `A → ε` as an empty body.
+    fn body(production: &Production) -> &[Symbol] {
+        if production.is_epsilon() {
+            &[]
+        } else {
+            &production.rhs
+        }
+    }
+
+    /// Builds the Earley chart for the input symbols.
+    ///
+    /// # Algorithm
+    /// For each set, repeatedly apply until no new items appear:
+    /// - Predict: for [A → α•Bβ, i], add [B → •γ, j] (and advance over B if nullable)
+    /// - Scan: for [A → α•aβ, i] with a = input[j], add [A → αa•β, i] to set j+1
+    /// - Complete: for [B → γ•, i], advance every [A → α•Bβ, k] in set i
+    fn build_chart(&self, input: &[Symbol]) -> Chart {
+        let productions = self.grammar.all_productions();
+        let start = self.grammar.start_symbol();
+
+        let mut chart: Chart = vec![Vec::new(); input.len() + 1];
+        let mut seen: Vec<HashSet<EarleyItem>> = vec![HashSet::new(); input.len() + 1];
+
+        for (index, production) in productions.iter().enumerate() {
+            if production.lhs == start && self.usable[index] {
+                let item = EarleyItem {
+                    production: index,
+                    dot: 0,
+                    origin: 0,
+                };
+                if seen[0].insert(item) {
+                    chart[0].push(item);
+                }
+            }
+        }
+
+        for position in 0..=input.len() {
+            let mut cursor = 0;
+            while cursor < chart[position].len() {
+                let item = chart[position][cursor];
+                cursor += 1;
+
+                let body = Self::body(&productions[item.production]);
+                let mut added = Vec::new();
+
+                match body.get(item.dot) {
+                    Some(&next) if next.is_nonterminal() => {
+                        // Predict
+                        for (index, production) in productions.iter().enumerate() {
+                            if production.lhs == next && self.usable[index] {
+                                added.push(EarleyItem {
+                                    production: index,
+                                    dot: 0,
+                                    origin: position,
+                                });
+                            }
+                        }
+                        if self.nullable.contains(&next) {
+                            added.push(EarleyItem {
+                                dot: item.dot + 1,
+                                ..item
+                            });
+                        }
+                    }
+                    Some(&next) => {
+                        // Scan
+                        if position < input.len() && input[position] == next {
+                            let scanned = EarleyItem {
+                                dot: item.dot + 1,
+                                ..item
+                            };
+                            if seen[position + 1].insert(scanned) {
+                                chart[position + 1].push(scanned);
+                            }
+                        }
+                    }
+                    None => {
+                        // Complete
+                        let lhs = productions[item.production].lhs;
+                        for waiting in &chart[item.origin] {
+                            let waiting_body = Self::body(&productions[waiting.production]);
+                            if waiting_body.get(waiting.dot) == Some(&lhs) {
+                                added.push(EarleyItem {
+                                    dot: waiting.dot + 1,
+                                    ..*waiting
+                                });
+                            }
+                        }
+                    }
+                }
+
+                for new_item in added {
+                    if seen[position].insert(new_item) {
+                        chart[position].push(new_item);
+                    }
+                }
+            }
+        }
+
+        chart
+    }
+
+    /// Checks whether the input string belongs to the language of the grammar.
+    pub fn parse(&self, input: &str) -> bool {
+        let symbols = self.grammar.config().string_to_symbols(input);
+        let chart = self.build_chart(&symbols);
+        self.accepts_chart(&chart, symbols.len())
+    }
+
+    /// Checks whether the input is a prefix of some string in the language of
+    /// the grammar; every string in the language is also a prefix.
+    ///
+    /// The chart has the correct-prefix property: the last item set is
+    /// non-empty exactly when some item can still be completed into a parse,
+    /// since productions with unproductive nonterminals are never predicted.
+    /// Unlike [`SLR1Parser::accepts_prefix`](crate::slr1::SLR1Parser::accepts_prefix)
+    /// this works for any grammar, ambiguous ones included.
+    pub fn recognize_prefix(&self, input: &str) -> bool {
+        let symbols = self.grammar.config().string_to_symbols(input);
+        let chart = self.build_chart(&symbols);
+        !chart[symbols.len()].is_empty()
+    }
+
+    fn accepts_chart(&self, chart: &Chart, length: usize) -> bool {
+        let productions = self.grammar.all_productions();
+        let start = self.grammar.start_symbol();
+
+        chart[length].iter().any(|item| {
+            let production = &productions[item.production];
+            item.origin == 0 && production.lhs == start && item.dot == Self::body(production).len()
+        })
+    }
+
+    /// Parses the input and returns the shared packed parse forest rooted at
+    /// the start symbol, or `None` if the input is rejected.
+    ///
+    /// Cyclic derivations (A ⇒⁺ A over the same span) are omitted so that the
+    /// forest, and every tree enumerated from it, is finite.
+    pub fn parse_forest(&self, input: &str) -> Option<SppfNode> {
+        let symbols = self.grammar.config().string_to_symbols(input);
+        let chart = self.build_chart(&symbols);
+        if !self.accepts_chart(&chart, symbols.len()) {
+            return None;
+        }
+
+        // Map (nonterminal, start) to every end position it was completed at
+        let productions = self.grammar.all_productions();
+        let mut completed: HashMap<(Symbol, usize), Vec<usize>> = HashMap::new();
+        for (end, set) in chart.iter().enumerate() {
+            for item in set {
+                let production = &productions[item.production];
+                if item.dot == Self::body(production).len() {
+                    let ends = completed.entry((production.lhs, item.origin)).or_default();
+                    if !ends.contains(&end) {
+                        ends.push(end);
+                    }
+                }
+            }
+        }
+
+        let mut builder = ForestBuilder {
+            grammar: &self.grammar,
+            input: &symbols,
+            completed,
+            memo: HashMap::new(),
+            in_progress: HashSet::new(),
+        };
+        let (root, _) = builder.node(self.grammar.start_symbol(), 0, symbols.len());
+        root.map(|root| Rc::try_unwrap(root).unwrap_or_else(|shared| (*shared).clone()))
+    }
+
+    /// Checks whether the input has more than one parse tree.
+    ///
+    /// Rejected inputs are not ambiguous. Like [`parse_forest`](Self::parse_forest),
+    /// cyclic derivations are not counted as extra trees.
+    pub fn is_ambiguous(&self, input: &str) -> bool {
+        self.parse_forest(input)
+            .is_some_and(|forest| forest.count_trees(2) == 2)
+    }
+
+    /// Returns every ambiguous input of at most `max_len` terminals, shortest
+    /// first and then in symbol order; the first entry is a shortest witness
+    /// of the grammar's ambiguity.
+    ///
+    /// The search is exhaustive over the terminal alphabet, so its cost grows
+    /// exponentially with `max_len`. An empty result means no input up to
+    /// that length is ambiguous, not that the grammar is unambiguous.
+    pub fn ambiguous_inputs(&self, max_len: usize) -> Vec<String> {
+        // Inputs are read verbatim, so terminals are spelled by their characters
+        let mut terminals: Vec<char> = self
+            .grammar
+            .terminals()
+            .iter()
+            .filter_map(Symbol::as_char)
+            .collect();
+        terminals.sort();
+
+        let mut result = Vec::new();
+        let mut layer: Vec<String> = vec![String::new()];
+        for len in 0..=max_len {
+            result.extend(
+                layer
+                    .iter()
+                    .filter(|input| self.is_ambiguous(input))
+                    .cloned(),
+            );
+            if len == max_len {
+                break;
+            }
+            layer = layer
+                .iter()
+                .flat_map(|prefix| terminals.iter().map(move |t| format!("{}{}", prefix, t)))
+                .collect();
+        }
+
+        result
+    }
+}
+
+/// Builds SPPF nodes on demand from the completed spans of an Earley chart.
+struct ForestBuilder<'a> {
+    grammar: &'a Grammar,
+    input: &'a [Symbol],
+    completed: HashMap<(Symbol, usize), Vec<usize>>,
+    memo: HashMap<(Symbol, usize, usize), Rc<SppfNode>>,
+    in_progress: HashSet<(Symbol, usize, usize)>,
+}
+
+impl ForestBuilder<'_> {
+    /// Builds the node for `symbol` over `start..end`, or `None` if the span
+    /// has no derivation without a cycle through a node under construction.
+    ///
+    /// Also returns whether a cycle was cut while building it. Such a node
+    /// leaves out the trees through the nodes under construction at the
+    /// time, so it is not memoized: elsewhere the same span may have other
+    /// trees, and its trees may repeat a node under construction there.
+    fn node(&mut self, symbol: Symbol, start: usize, end: usize) -> (Option<Rc<SppfNode>>, bool) {
+        let key = (symbol, start, end);
+        if let Some(node) = self.memo.get(&key) {
+            return (Some(Rc::clone(node)), false);
+        }
+
+        if !symbol.is_nonterminal() {
+            let leaf = Rc::new(SppfNode {
+                symbol,
+                start,
+                end,
+                families: Vec::new(),
+            });
+            self.memo.insert(key, Rc::clone(&leaf));
+            return (Some(leaf), false);
+        }
+
+        self.in_progress.insert(key);
+        let mut cut = false;
+        let mut families = Vec::new();
+        for production in self.grammar.get_productions(symbol) {
+            let body = EarleyParser::body(production);
+            let mut splits = Vec::new();
+            self.splits(body, start, end, &mut Vec::new(), &mut splits);
+
+            'split: for spans in splits {
+                let mut children = Vec::new();
+                for (child_symbol, child_start, child_end) in spans {
+                    if self
+                        .in_progress
+                        .contains(&(child_symbol, child_start, child_end))
+                    {
+                        cut = true;
+                        continue 'split;
+                    }
+                    let (child, child_cut) = self.node(child_symbol, child_start, child_end);
+                    cut |= child_cut;
+                    // A child without cycle-free derivations is no leaf
+                    match child {
+                        Some(child) => children.push(child),
+                        None => continue 'split,
+                    }
+                }
+                families.push(PackedNode {
+                    production: production.clone(),
+                    children,
+                });
+            }
+        }
+        self.in_progress.remove(&key);
+
+        let node = (!families.is_empty()).then(|| {
+            Rc::new(SppfNode {
+                symbol,
+                start,
+                end,
+                families,
+            })
+        });
+        if !cut && let Some(node) = &node {
+            self.memo.insert(key, Rc::clone(node));
+        }
+        (node, cut)
+    }
+
+    /// Enumerates every way of splitting `position..end` among the body symbols.
+    fn splits(
+        &self,
+        body: &[Symbol],
+        position: usize,
+        end: usize,
+        current: &mut Vec<(Symbol, usize, usize)>,
+        result: &mut Vec<Vec<(Symbol, usize, usize)>>,
+    ) {
+        let Some((&first, rest)) = body.split_first() else {
+            if position == end {
+                result.push(current.clone());
+            }
+            return;
+        };
+
+        if first.is_nonterminal() {
+            let ends = self
+                .completed
+                .get(&(first, position))
+                .cloned()
+                .unwrap_or_default();
+            for next in ends {
+                if next <= end {
+                    current.push((first, position, next));
+                    self.splits(rest, next, end, current, result);
+                    current.pop();
+                }
+            }
+        } else if position < end && self.input[position] == first {
+            current.push((first, position, position + 1));
+            self.splits(rest, position + 1, end, current, result);
+            current.pop();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for the CFG parser.
 
+use crate::trace::Rejection;
 use thiserror::Error;
 
 /// Errors that can occur during grammar parsing and analysis.
@@ -8,8 +9,15 @@ pub enum GrammarError {
     #[error("Invalid grammar format: {0}")]
     InvalidFormat(String),
 
-    #[error("Invalid production format: {0}")]
-    InvalidProduction(String),
+    #[error("line {line}: invalid production: {message}")]
+    InvalidProduction {
+        /// The 1-based line. For [`Grammar::parse`](crate::grammar::Grammar::parse)
+        /// this counts production lines, leaving out the count line, as
+        /// [`lint`](crate::grammar::lint) does; for `;`-terminated grammars it
+        /// is the source line
+        line: usize,
+        message: String,
+    },
 
     #[error("Empty grammar input")]
     EmptyInput,
@@ -17,6 +25,9 @@ pub enum GrammarError {
     #[error("Not enough production lines: expected {expected}, got {actual}")]
     NotEnoughProductions { expected: usize, actual: usize },
 
+    #[error("Too many production lines: expected {expected}, got {actual}")]
+    TooManyProductions { expected: usize, actual: usize },
+
     #[error("LL(1) conflict at M[{nonterminal}, {terminal}]:\n  {prod1}\n  {prod2}")]
     LL1Conflict {
         nonterminal: String,
@@ -25,8 +36,17 @@ pub enum GrammarError {
         prod2: String,
     },
 
-    #[error("SLR(1) Shift/Reduce conflict at state {state}, symbol {symbol}")]
-    SLR1ShiftReduceConflict { state: usize, symbol: String },
+    #[error(
+        "SLR(1) Shift/Reduce conflict at state {state}, symbol {symbol}{}",
+        epsilon_reduce_note(*.epsilon_reduce)
+    )]
+    SLR1ShiftReduceConflict {
+        state: usize,
+        symbol: String,
+        /// Whether the reduction is by an ε-production (`A → •`), in which
+        /// case removing ε-productions may resolve the conflict
+        epsilon_reduce: bool,
+    },
 
     #[error(
         "SLR(1) Reduce/Reduce conflict at state {state}, symbol {symbol}:\n  {prod1}\n  {prod2}"
@@ -38,6 +58,32 @@ pub enum GrammarError {
         prod2: String,
     },
 
+    #[error("{conflict}\nItems of state {state}:{}", format_items(.items))]
+    ConflictInState {
+        conflict: Box<GrammarError>,
+        state: usize,
+        /// The items of the state, rendered as `A → α•β`
+        items: Vec<String>,
+    },
+
+    #[error("Inconsistent FIRST/FOLLOW sets: {0}")]
+    InconsistentSets(String),
+
+    #[error("{}", format_conflict_report(.conflicts))]
+    ConflictReport { conflicts: Vec<GrammarError> },
+
+    #[error("Input may only contain terminals: found {symbol} at position {position}")]
+    NonterminalInInput { position: usize, symbol: String },
+
+    #[error("Input symbol {symbol} at position {position} is not a terminal of the grammar")]
+    UnknownTerminal { position: usize, symbol: String },
+
+    #[error("{0}")]
+    Rejected(Rejection),
+
+    #[error("Parse stack exceeded {limit} symbols at input position {position}")]
+    StackLimitExceeded { limit: usize, position: usize },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -45,5 +91,28 @@ pub enum GrammarError {
     ParseError(String),
 }
 
+/// Points at ε-production removal when a conflict reduces by one.
+fn epsilon_reduce_note(epsilon_reduce: bool) -> &'static str {
+    if epsilon_reduce {
+        " (reduce by an ε-production; removing ε-productions may help)"
+    } else {
+        ""
+    }
+}
+
+/// Formats the items of a state, one indented item per line.
+fn format_items(items: &[String]) -> String {
+    items.iter().map(|item| format!("\n  {}", item)).collect()
+}
+
+/// Formats the conflicts of a report as a numbered list.
+fn format_conflict_report(conflicts: &[GrammarError]) -> String {
+    let mut report = format!("{} conflict(s) found:", conflicts.len());
+    for (i, conflict) in conflicts.iter().enumerate() {
+        report.push_str(&format!("\n{}. {}", i + 1, conflict));
+    }
+    report
+}
+
 /// Type alias for Results in this crate.
 pub type Result<T> = std::result::Result<T, GrammarError>;
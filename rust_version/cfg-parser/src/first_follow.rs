@@ -3,15 +3,102 @@
 //! This module implements algorithms from Aho et al., "Compilers: Principles,
 //! Techniques, and Tools" (2nd Edition), sections 4.4.
 
-use crate::grammar::Grammar;
+use crate::error::{GrammarError, Result};
+use crate::grammar::{Grammar, GrammarConfig, Production};
 use crate::symbol::Symbol;
+use crate::trace::render_markdown;
 use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::LazyLock;
 
-/// Type alias for FIRST sets mapping.
-pub type FirstSets = HashMap<Symbol, HashSet<Symbol>>;
+/// The set returned for symbols missing from a FIRST or FOLLOW mapping.
+static EMPTY_SET: LazyLock<HashSet<Symbol>> = LazyLock::new(HashSet::new);
 
-/// Type alias for FOLLOW sets mapping.
-pub type FollowSets = HashMap<Symbol, HashSet<Symbol>>;
+/// FIRST sets of the symbols of a grammar.
+///
+/// Dereferences to the underlying map; [`first_of`](Self::first_of) treats
+/// missing symbols as having an empty set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FirstSets(HashMap<Symbol, HashSet<Symbol>>);
+
+impl FirstSets {
+    /// Returns FIRST(symbol), or the empty set if the symbol is unknown.
+    pub fn first_of(&self, symbol: Symbol) -> &HashSet<Symbol> {
+        self.0.get(&symbol).unwrap_or(&EMPTY_SET)
+    }
+
+    /// Checks if `s` is in FIRST(symbol).
+    pub fn contains(&self, symbol: Symbol, s: Symbol) -> bool {
+        self.first_of(symbol).contains(&s)
+    }
+}
+
+impl Deref for FirstSets {
+    type Target = HashMap<Symbol, HashSet<Symbol>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FirstSets {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<HashMap<Symbol, HashSet<Symbol>>> for FirstSets {
+    fn from(map: HashMap<Symbol, HashSet<Symbol>>) -> Self {
+        Self(map)
+    }
+}
+
+/// FOLLOW sets of the nonterminals of a grammar.
+///
+/// Dereferences to the underlying map; [`follow_of`](Self::follow_of) treats
+/// missing symbols as having an empty set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FollowSets(HashMap<Symbol, HashSet<Symbol>>);
+
+impl FollowSets {
+    /// Returns FOLLOW(symbol), or the empty set if the symbol is unknown.
+    pub fn follow_of(&self, symbol: Symbol) -> &HashSet<Symbol> {
+        self.0.get(&symbol).unwrap_or(&EMPTY_SET)
+    }
+
+    /// Checks if `s` is in FOLLOW(symbol).
+    pub fn contains(&self, symbol: Symbol, s: Symbol) -> bool {
+        self.follow_of(symbol).contains(&s)
+    }
+}
+
+impl Deref for FollowSets {
+    type Target = HashMap<Symbol, HashSet<Symbol>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for FollowSets {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<HashMap<Symbol, HashSet<Symbol>>> for FollowSets {
+    fn from(map: HashMap<Symbol, HashSet<Symbol>>) -> Self {
+        Self(map)
+    }
+}
+
+/// Type alias for FIRST_k sets: each symbol maps to the terminal strings of
+/// length at most k that its derivations can start with.
+pub type FirstKSets = HashMap<Symbol, HashSet<Vec<Symbol>>>;
+
+/// Type alias for FOLLOW_k sets: each nonterminal maps to the terminal
+/// strings of length at most k (ending in `$` when shorter) that can follow it.
+pub type FollowKSets = HashMap<Symbol, HashSet<Vec<Symbol>>>;
 
 /// Computes the FIRST sets for all symbols in the grammar.
 ///
@@ -24,7 +111,7 @@ pub type FollowSets = HashMap<Symbol, HashSet<Symbol>>;
 ///    - If ε ∈ FIRST(Xᵢ) for all i, add ε to FIRST(A)
 /// 3. Repeat until no changes (fixed-point iteration)
 pub fn compute_first_sets(grammar: &Grammar) -> FirstSets {
-    let mut first_sets: FirstSets = HashMap::new();
+    let mut first_sets = FirstSets::default();
 
     // Initialize FIRST sets for terminals
     for terminal in grammar.terminals() {
@@ -72,6 +159,9 @@ pub fn compute_first_sets(grammar: &Grammar) -> FirstSets {
 /// - If ε ∈ FIRST(X₁), add FIRST(X₂) - {ε}
 /// - Continue while ε ∈ FIRST(Xᵢ)
 /// - If ε ∈ FIRST(Xᵢ) for all i, add ε to result
+///
+/// A terminal is its own FIRST set and never nullable, so it ends the scan
+/// even when `first_sets` has no entry for it.
 pub fn first_of_string(first_sets: &FirstSets, symbols: &[Symbol]) -> HashSet<Symbol> {
     let mut result = HashSet::new();
     let mut has_epsilon = true;
@@ -81,10 +171,16 @@ pub fn first_of_string(first_sets: &FirstSets, symbols: &[Symbol]) -> HashSet<Sy
             break;
         }
 
-        let first_sym = first_sets.get(symbol).cloned().unwrap_or_default();
+        if symbol.is_terminal() || *symbol == Symbol::EndMarker {
+            result.insert(*symbol);
+            has_epsilon = false;
+            break;
+        }
+
+        let first_sym = first_sets.first_of(*symbol);
 
         // Add FIRST(symbol) - {ε}
-        for sym in &first_sym {
+        for sym in first_sym {
             if !sym.is_epsilon() {
                 result.insert(*sym);
             }
@@ -102,6 +198,55 @@ pub fn first_of_string(first_sets: &FirstSets, symbols: &[Symbol]) -> HashSet<Sy
     result
 }
 
+/// Computes FIRST of the suffix `rhs[from..]` of a production's body.
+///
+/// An empty suffix (`from` at or past the end of the body) yields `{ε}`, as
+/// does the body of an ε-production.
+pub fn first_of_suffix(
+    first_sets: &FirstSets,
+    production: &Production,
+    from: usize,
+) -> HashSet<Symbol> {
+    first_of_string(first_sets, production.rhs.get(from..).unwrap_or(&[]))
+}
+
+/// Returns FIRST(symbol) for a grammar.
+///
+/// A convenience for one-off queries: the full FIRST sets are computed and
+/// the one asked for is returned, so prefer [`compute_first_sets`] when
+/// querying many symbols.
+pub fn first_of(grammar: &Grammar, symbol: Symbol) -> HashSet<Symbol> {
+    compute_first_sets(grammar).first_of(symbol).clone()
+}
+
+/// Returns FOLLOW(symbol) for a grammar.
+///
+/// Like [`first_of`], this computes the full FIRST and FOLLOW sets; prefer
+/// [`compute_follow_sets`] when querying many symbols.
+pub fn follow_of(grammar: &Grammar, symbol: Symbol) -> HashSet<Symbol> {
+    let first_sets = compute_first_sets(grammar);
+    compute_follow_sets(grammar, &first_sets)
+        .follow_of(symbol)
+        .clone()
+}
+
+/// Computes the set of nonterminals that can derive ε.
+///
+/// A nonterminal is nullable exactly when ε ∈ FIRST(A).
+pub fn nullable_symbols(grammar: &Grammar) -> HashSet<Symbol> {
+    let first_sets = compute_first_sets(grammar);
+    grammar
+        .nonterminals()
+        .iter()
+        .filter(|nt| {
+            first_sets
+                .get(nt)
+                .is_some_and(|first| first.contains(&Symbol::Epsilon))
+        })
+        .copied()
+        .collect()
+}
+
 /// Computes the FOLLOW sets for all nonterminals in the grammar.
 ///
 /// # Algorithm
@@ -111,7 +256,7 @@ pub fn first_of_string(first_sets: &FirstSets, symbols: &[Symbol]) -> HashSet<Sy
 ///    - If ε ∈ FIRST(β) or β = ε, add FOLLOW(A) to FOLLOW(B)
 /// 3. Repeat until no changes (fixed-point iteration)
 pub fn compute_follow_sets(grammar: &Grammar, first_sets: &FirstSets) -> FollowSets {
-    let mut follow_sets: FollowSets = HashMap::new();
+    let mut follow_sets = FollowSets::default();
 
     // Initialize all nonterminals with empty sets
     for nonterminal in grammar.nonterminals() {
@@ -144,11 +289,9 @@ pub fn compute_follow_sets(grammar: &Grammar, first_sets: &FirstSets) -> FollowS
                 let current_follow = follow_sets.get(symbol).unwrap().clone();
                 let mut new_follow = current_follow.clone();
 
-                // Get the rest of the production after this symbol
+                // Compute FIRST(β) for the rest of the production
                 let beta = &rhs[i + 1..];
-
-                // Compute FIRST(β)
-                let first_beta = first_of_string(first_sets, beta);
+                let first_beta = first_of_suffix(first_sets, production, i + 1);
 
                 // Add FIRST(β) - {ε} to FOLLOW(symbol)
                 for sym in &first_beta {
@@ -173,3 +316,283 @@ pub fn compute_follow_sets(grammar: &Grammar, first_sets: &FirstSets) -> FollowS
 
     follow_sets
 }
+
+/// Computes FIRST_k sets for all symbols in the grammar.
+///
+/// # Algorithm
+/// The generalization of FIRST to k symbols of lookahead:
+/// - FIRST_k(a) = {a} for terminals and FIRST_k(ε) = {ε} (the empty string)
+/// - For A → X₁X₂...Xₙ, add FIRST_k(X₁) ⊕ₖ ... ⊕ₖ FIRST_k(Xₙ) to FIRST_k(A),
+///   where ⊕ₖ concatenates every pair of strings and truncates to length k
+/// - Repeat until no set changes
+///
+/// With k = 1 this matches [`compute_first_sets`], with ε as the empty string.
+pub fn compute_first_sets_k(grammar: &Grammar, k: usize) -> FirstKSets {
+    let mut first_sets: FirstKSets = HashMap::new();
+
+    for terminal in grammar.terminals() {
+        first_sets.insert(*terminal, HashSet::from([vec![*terminal]]));
+    }
+    first_sets.insert(Symbol::Epsilon, HashSet::from([Vec::new()]));
+    first_sets.insert(Symbol::EndMarker, HashSet::from([vec![Symbol::EndMarker]]));
+    for nonterminal in grammar.nonterminals() {
+        first_sets.insert(*nonterminal, HashSet::new());
+    }
+
+    // Fixed-point iteration
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for production in grammar.all_productions() {
+            let rhs_first = first_k_of_string(&first_sets, &production.rhs, k);
+            let current = first_sets.get_mut(&production.lhs).unwrap();
+            for string in rhs_first {
+                changed |= current.insert(string);
+            }
+        }
+    }
+
+    first_sets
+}
+
+/// Computes FIRST_k of a string of symbols.
+pub fn first_k_of_string(
+    first_sets: &FirstKSets,
+    symbols: &[Symbol],
+    k: usize,
+) -> HashSet<Vec<Symbol>> {
+    let mut result = HashSet::from([Vec::new()]);
+
+    for symbol in symbols {
+        // Strings already k long are unaffected by what follows
+        if result.iter().all(|string| string.len() >= k) {
+            break;
+        }
+        let first_sym = first_sets.get(symbol).cloned().unwrap_or_default();
+        result = concat_k(&result, &first_sym, k);
+    }
+
+    result
+}
+
+/// Computes FOLLOW_k sets for all nonterminals in the grammar.
+///
+/// # Algorithm
+/// - Initialize FOLLOW_k(S) = {$}
+/// - For A → αBβ, add FIRST_k(β) ⊕ₖ FOLLOW_k(A) to FOLLOW_k(B)
+/// - Repeat until no set changes
+pub fn compute_follow_sets_k(grammar: &Grammar, first_sets: &FirstKSets, k: usize) -> FollowKSets {
+    let mut follow_sets: FollowKSets = HashMap::new();
+
+    for nonterminal in grammar.nonterminals() {
+        follow_sets.insert(*nonterminal, HashSet::new());
+    }
+    follow_sets
+        .get_mut(&grammar.start_symbol())
+        .unwrap()
+        .insert(vec![Symbol::EndMarker]);
+
+    // Fixed-point iteration
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for production in grammar.all_productions() {
+            let follow_lhs = follow_sets.get(&production.lhs).unwrap().clone();
+
+            for (i, symbol) in production.rhs.iter().enumerate() {
+                if !symbol.is_nonterminal() {
+                    continue;
+                }
+
+                let first_beta = first_k_of_string(first_sets, &production.rhs[i + 1..], k);
+                let current = follow_sets.get_mut(symbol).unwrap();
+                for string in concat_k(&first_beta, &follow_lhs, k) {
+                    changed |= current.insert(string);
+                }
+            }
+        }
+    }
+
+    follow_sets
+}
+
+/// Concatenates every string of `prefixes` with every string of `suffixes`,
+/// truncating the results to length k.
+pub(crate) fn concat_k(
+    prefixes: &HashSet<Vec<Symbol>>,
+    suffixes: &HashSet<Vec<Symbol>>,
+    k: usize,
+) -> HashSet<Vec<Symbol>> {
+    let mut result = HashSet::new();
+
+    for prefix in prefixes {
+        if prefix.len() >= k {
+            result.insert(prefix[..k].to_vec());
+            continue;
+        }
+        for suffix in suffixes {
+            let mut string = prefix.clone();
+            string.extend(suffix.iter().take(k - prefix.len()));
+            result.insert(string);
+        }
+    }
+
+    result
+}
+
+/// Checks FIRST and FOLLOW sets against invariants every correct computation
+/// satisfies.
+///
+/// - FIRST(a) = {a} for every terminal a
+/// - FIRST sets of nonterminals contain only terminals and ε
+/// - `$` ∈ FOLLOW(S) for the start symbol S
+/// - FOLLOW sets contain only terminals and `$`
+///
+/// A violation means the sets were computed wrongly (or tampered with); the
+/// first one found is returned as [`GrammarError::InconsistentSets`].
+pub fn validate(grammar: &Grammar, first_sets: &FirstSets, follow_sets: &FollowSets) -> Result<()> {
+    let inconsistent = |message: String| Err(GrammarError::InconsistentSets(message));
+
+    let mut terminals: Vec<Symbol> = grammar.terminals().iter().copied().collect();
+    terminals.sort();
+    for terminal in terminals {
+        let first = first_sets.first_of(terminal);
+        if first.len() != 1 || !first.contains(&terminal) {
+            return inconsistent(format!(
+                "FIRST({}) = {} instead of {{{}}}",
+                terminal,
+                format_set(first, grammar.config()),
+                terminal
+            ));
+        }
+    }
+
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+    for &nt in &nonterminals {
+        let first = first_sets.first_of(nt);
+        if first.iter().any(|s| !s.is_terminal() && !s.is_epsilon()) {
+            return inconsistent(format!(
+                "FIRST({}) = {} contains a symbol other than terminals and ε",
+                nt,
+                format_set(first, grammar.config())
+            ));
+        }
+    }
+
+    let start = grammar.start_symbol();
+    if !follow_sets.contains(start, Symbol::EndMarker) {
+        return inconsistent(format!(
+            "FOLLOW({}) = {} is missing $ for the start symbol",
+            start,
+            format_set(follow_sets.follow_of(start), grammar.config())
+        ));
+    }
+
+    for &nt in &nonterminals {
+        let follow = follow_sets.follow_of(nt);
+        if follow
+            .iter()
+            .any(|s| !s.is_terminal() && !s.is_end_marker())
+        {
+            return inconsistent(format!(
+                "FOLLOW({}) = {} contains a symbol other than terminals and $",
+                nt,
+                format_set(follow, grammar.config())
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats a set of symbols as `{a, b, $}` in symbol order, spelling the
+/// end marker as the configuration does.
+pub fn format_set(set: &HashSet<Symbol>, config: &GrammarConfig) -> String {
+    let mut symbols: Vec<Symbol> = set.iter().copied().collect();
+    symbols.sort();
+    let items: Vec<String> = symbols
+        .iter()
+        .map(|s| config.symbol_to_string(*s))
+        .collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+/// Formats FIRST sets as one `FIRST(A) = {...}` line per nonterminal, in
+/// symbol order.
+pub fn format_first_sets(grammar: &Grammar, first_sets: &FirstSets) -> String {
+    format_sets("FIRST", grammar, first_sets)
+}
+
+/// Formats FOLLOW sets as one `FOLLOW(A) = {...}` line per nonterminal, in
+/// symbol order.
+pub fn format_follow_sets(grammar: &Grammar, follow_sets: &FollowSets) -> String {
+    format_sets("FOLLOW", grammar, follow_sets)
+}
+
+/// Renders the FIRST and FOLLOW sets as a LaTeX `tabular`, one row per
+/// nonterminal in symbol order.
+pub fn to_latex(grammar: &Grammar, first_sets: &FirstSets, follow_sets: &FollowSets) -> String {
+    let config = grammar.config();
+    let latex_set = |set: &HashSet<Symbol>| -> String {
+        let mut symbols: Vec<Symbol> = set.iter().copied().collect();
+        symbols.sort();
+        let items: Vec<String> = symbols.iter().map(|s| config.symbol_to_latex(*s)).collect();
+        format!("$\\{{{}\\}}$", items.join(", "))
+    };
+
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+
+    let mut output =
+        String::from("\\begin{tabular}{lll}\nNonterminal & FIRST & FOLLOW \\\\\n\\hline\n");
+    for nt in nonterminals {
+        output.push_str(&format!(
+            "${}$ & {} & {} \\\\\n",
+            config.symbol_to_latex(nt),
+            latex_set(first_sets.first_of(nt)),
+            latex_set(follow_sets.follow_of(nt))
+        ));
+    }
+    output.push_str("\\end{tabular}\n");
+    output
+}
+
+/// Renders the FIRST and FOLLOW sets as a GitHub-flavored Markdown table,
+/// one row per nonterminal in symbol order.
+pub fn to_markdown_table(
+    grammar: &Grammar,
+    first_sets: &FirstSets,
+    follow_sets: &FollowSets,
+) -> String {
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+
+    let mut rows = vec![vec![
+        "Nonterminal".to_string(),
+        "FIRST".to_string(),
+        "FOLLOW".to_string(),
+    ]];
+    for nt in nonterminals {
+        rows.push(vec![
+            nt.to_string(),
+            format_set(first_sets.first_of(nt), grammar.config()),
+            format_set(follow_sets.follow_of(nt), grammar.config()),
+        ]);
+    }
+    render_markdown(&rows)
+}
+
+fn format_sets(name: &str, grammar: &Grammar, sets: &HashMap<Symbol, HashSet<Symbol>>) -> String {
+    let mut nonterminals: Vec<Symbol> = grammar.nonterminals().iter().copied().collect();
+    nonterminals.sort();
+
+    let mut output = String::new();
+    for nt in nonterminals {
+        let set = format_set(sets.get(&nt).unwrap_or(&EMPTY_SET), grammar.config());
+        output.push_str(&format!("{}({}) = {}\n", name, nt, set));
+    }
+    output
+}
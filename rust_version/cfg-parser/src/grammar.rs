@@ -3,10 +3,21 @@
 //! This module provides data structures and parsing logic for working with
 //! context-free grammars, including productions and grammar representation.
 
+use crate::analysis::shortest_difference_from;
+use crate::earley::EarleyParser;
 use crate::error::{GrammarError, Result};
-use crate::symbol::{string_to_symbols, symbols_to_string, Symbol};
-use std::collections::{HashMap, HashSet};
+use crate::first_follow::{
+    compute_first_sets, compute_first_sets_k, compute_follow_sets, compute_follow_sets_k, concat_k,
+    first_k_of_string, nullable_symbols, FirstSets, FollowSets,
+};
+use crate::ll1::{self, LL1Parser};
+use crate::slr1::SLR1Parser;
+use crate::symbol::{resolve_escape, symbols_to_string, Symbol};
+use crate::trace::check_input;
+use crate::transform::{remove_useless_symbols, GrammarPipeline, NameGen};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
+use std::io::{BufRead, Lines};
 
 /// A production rule in a context-free grammar.
 ///
@@ -25,11 +36,23 @@ impl Production {
     pub fn new(lhs: Symbol, rhs: Vec<Symbol>) -> Self {
         Self { lhs, rhs }
     }
+
+    /// Checks if this is an ε-production (A → ε).
+    #[inline]
+    pub fn is_epsilon(&self) -> bool {
+        self.rhs == [Symbol::Epsilon]
+    }
+
+    /// Checks if this is a unit production (A → B with B a nonterminal).
+    #[inline]
+    pub fn is_unit(&self) -> bool {
+        matches!(self.rhs.as_slice(), [symbol] if symbol.is_nonterminal())
+    }
 }
 
 impl fmt::Display for Production {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let rhs_str = if self.rhs == vec![Symbol::Epsilon] {
+        let rhs_str = if self.is_epsilon() {
             "ε".to_string()
         } else {
             symbols_to_string(&self.rhs)
@@ -38,6 +61,144 @@ impl fmt::Display for Production {
     }
 }
 
+/// Options controlling how grammar text and input strings are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarConfig {
+    /// The character that spells the end marker (default `$`)
+    ///
+    /// It is read as [`Symbol::EndMarker`]; when it is not `$`, a `$` in the
+    /// grammar or the input is an ordinary terminal.
+    pub end_marker: char,
+    /// Whether yacc-style `{ ... }` semantic actions are removed from
+    /// production lines before they are read (default `false`)
+    ///
+    /// When it is off, `{` and `}` are ordinary terminals.
+    pub strip_actions: bool,
+}
+
+impl Default for GrammarConfig {
+    fn default() -> Self {
+        Self {
+            end_marker: '$',
+            strip_actions: false,
+        }
+    }
+}
+
+impl GrammarConfig {
+    /// Returns this configuration with a different end-marker character.
+    pub fn with_end_marker(mut self, end_marker: char) -> Self {
+        self.end_marker = end_marker;
+        self
+    }
+
+    /// Returns this configuration with semantic-action stripping turned on
+    /// or off.
+    pub fn with_strip_actions(mut self, strip_actions: bool) -> Self {
+        self.strip_actions = strip_actions;
+        self
+    }
+
+    /// Converts a character to a symbol, honoring the configured end marker.
+    pub fn symbol_from_char(&self, c: char) -> Symbol {
+        if c == self.end_marker {
+            Symbol::EndMarker
+        } else if c == '$' {
+            Symbol::Terminal('$')
+        } else {
+            Symbol::from_char(c)
+        }
+    }
+
+    /// Converts a string to symbols, honoring the configured end marker.
+    pub fn string_to_symbols(&self, s: &str) -> Vec<Symbol> {
+        s.chars().map(|c| self.symbol_from_char(c)).collect()
+    }
+
+    /// Converts the text of a production body to symbols, resolving the
+    /// escape sequences of [`unescape`](crate::symbol::unescape) and reading `\E` as the `error` token.
+    pub fn body_to_symbols(&self, text: &str) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let escaped = match chars.peek() {
+                Some('E') if c == '\\' => Some(Symbol::Error),
+                Some(&next) if c == '\\' => resolve_escape(next).map(Symbol::Terminal),
+                _ => None,
+            };
+            match escaped {
+                Some(symbol) => {
+                    chars.next();
+                    symbols.push(symbol);
+                }
+                None => symbols.push(self.symbol_from_char(c)),
+            }
+        }
+        symbols
+    }
+
+    /// Renders a symbol, spelling the end marker with the configured character.
+    pub fn symbol_to_string(&self, symbol: Symbol) -> String {
+        match symbol {
+            Symbol::EndMarker => self.end_marker.to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Renders a symbol for LaTeX math mode: ε becomes `\varepsilon` and
+    /// characters special to LaTeX are escaped.
+    pub fn symbol_to_latex(&self, symbol: Symbol) -> String {
+        let c = match symbol {
+            Symbol::Epsilon => return "\\varepsilon".to_string(),
+            Symbol::Special(label) => return format!("\\text{{{}}}", label),
+            Symbol::Error => return "\\text{error}".to_string(),
+            Symbol::EndMarker => self.end_marker,
+            Symbol::Terminal(c) | Symbol::Nonterminal(c) => c,
+        };
+        match c {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => format!("\\{}", c),
+            '\\' => "\\backslash".to_string(),
+            '~' => "\\sim".to_string(),
+            '^' => "\\hat{}".to_string(),
+            _ => c.to_string(),
+        }
+    }
+}
+
+/// A single-production edit to a grammar, see [`Grammar::apply_change`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarChange {
+    /// Add a production
+    Add(Production),
+    /// Remove a production
+    Remove(Production),
+    /// Replace a production in place
+    Replace { old: Production, new: Production },
+}
+
+impl GrammarChange {
+    /// Returns the productions touched by the change.
+    pub fn productions(&self) -> Vec<&Production> {
+        match self {
+            GrammarChange::Add(production) | GrammarChange::Remove(production) => {
+                vec![production]
+            }
+            GrammarChange::Replace { old, new } => vec![old, new],
+        }
+    }
+}
+
+/// How table exporters order their terminal columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnOrder {
+    /// Symbol order, as given by `Ord` on [`Symbol`]
+    #[default]
+    Symbol,
+    /// Order of first appearance in the grammar, see
+    /// [`Grammar::terminals_in_order`]
+    FirstAppearance,
+}
+
 /// A context-free grammar.
 ///
 /// Contains all productions, symbols, and provides methods for grammar analysis.
@@ -49,10 +210,13 @@ pub struct Grammar {
     nonterminals: HashSet<Symbol>,
     /// All terminal symbols
     terminals: HashSet<Symbol>,
-    /// The start symbol (always 'S')
+    /// The start symbol: 'S' for parsed grammars, but transformations and
+    /// [`set_start_symbol`](Self::set_start_symbol) may choose another
     start_symbol: Symbol,
-    /// Map from nonterminals to their productions
+    /// Map from nonterminals to their productions, each list in source order
     production_map: HashMap<Symbol, Vec<Production>>,
+    /// How the grammar text and input strings are read
+    config: GrammarConfig,
 }
 
 impl Grammar {
@@ -62,15 +226,27 @@ impl Grammar {
     /// - First line: number of nonterminals (n)
     /// - Next n lines: productions in format "A -> alpha beta gamma"
     ///   where alpha, beta, gamma are alternative productions separated by spaces
+    ///
+    /// Trailing blank lines are ignored, but any other line beyond the n
+    /// productions is rejected with [`GrammarError::TooManyProductions`] rather
+    /// than silently dropped, since it usually means the count is wrong.
+    ///
+    /// Files saved on Windows parse as well: a trailing `\r` is whitespace
+    /// like any other, and a UTF-8 byte order mark before the count is skipped.
     pub fn parse(lines: &[String]) -> Result<Self> {
+        Self::parse_with_config(lines, GrammarConfig::default())
+    }
+
+    /// Parses a grammar from input lines using the given configuration.
+    ///
+    /// The configuration is kept with the grammar, so the parsers built from
+    /// it read their input strings the same way.
+    pub fn parse_with_config(lines: &[String], config: GrammarConfig) -> Result<Self> {
         if lines.is_empty() {
             return Err(GrammarError::EmptyInput);
         }
 
-        let n = lines[0]
-            .trim()
-            .parse::<usize>()
-            .map_err(|e| GrammarError::InvalidFormat(format!("Invalid number: {}", e)))?;
+        let n = Self::parse_count(&lines[0])?;
 
         if lines.len() < n + 1 {
             return Err(GrammarError::NotEnoughProductions {
@@ -79,50 +255,294 @@ impl Grammar {
             });
         }
 
+        let actual = lines.len()
+            - 1
+            - lines
+                .iter()
+                .rev()
+                .take_while(|l| l.trim().is_empty())
+                .count();
+        if actual > n {
+            return Err(GrammarError::TooManyProductions {
+                expected: n,
+                actual,
+            });
+        }
+
         let mut all_productions = Vec::new();
 
-        // Parse each production line
-        for line in &lines[1..=n] {
-            let productions = Self::parse_production_line(line)?;
+        // Parse each production line; the first one is line 1
+        for (index, line) in lines[1..=n].iter().enumerate() {
+            let productions = Self::parse_production_line(line, index + 1, &config)?;
             all_productions.extend(productions);
         }
 
-        Self::from_productions(all_productions)
+        let mut grammar = Self::from_productions(all_productions)?;
+        grammar.config = config;
+        Ok(grammar)
+    }
+
+    /// Reads a grammar from a reader, in the format accepted by
+    /// [`parse`](Self::parse).
+    ///
+    /// Lines are read lazily: the count line, then that many production
+    /// lines, and what follows must be blank. Read failures are reported as
+    /// [`GrammarError::Io`].
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut lines = reader.lines();
+        let grammar_lines = Self::read_lines(&mut lines)?;
+        let n = grammar_lines.len() - 1;
+
+        // Blank lines only count once a production line follows them
+        let (mut actual, mut blank_run) = (n, 0);
+        for line in lines {
+            if line?.trim().is_empty() {
+                blank_run += 1;
+            } else {
+                actual += blank_run + 1;
+                blank_run = 0;
+            }
+        }
+        if actual > n {
+            return Err(GrammarError::TooManyProductions {
+                expected: n,
+                actual,
+            });
+        }
+
+        Self::parse(&grammar_lines)
+    }
+
+    /// Reads the count line and the production lines it announces, leaving
+    /// the rest of the input unread.
+    pub(crate) fn read_lines<R: BufRead>(lines: &mut Lines<R>) -> Result<Vec<String>> {
+        let first = lines.next().ok_or(GrammarError::EmptyInput)??;
+        let n = Self::parse_count(&first)?;
+
+        let mut grammar_lines = vec![first];
+        for _ in 0..n {
+            let line = lines.next().ok_or(GrammarError::NotEnoughProductions {
+                expected: n,
+                actual: grammar_lines.len() - 1,
+            })??;
+            grammar_lines.push(line);
+        }
+
+        Ok(grammar_lines)
+    }
+
+    /// Parses the count line: the number of production lines that follow.
+    ///
+    /// A UTF-8 byte order mark before the count, as some Windows editors
+    /// write, is skipped.
+    fn parse_count(line: &str) -> Result<usize> {
+        strip_bom(line)
+            .trim()
+            .parse::<usize>()
+            .map_err(|e| GrammarError::InvalidFormat(format!("Invalid number: {}", e)))
     }
 
     /// Parses a single production line.
     ///
     /// Format: "A -> alpha beta gamma"
-    /// Returns multiple productions (one for each alternative)
-    fn parse_production_line(line: &str) -> Result<Vec<Production>> {
-        let parts: Vec<&str> = line.split("->").collect();
+    /// Returns multiple productions (one for each alternative); an empty
+    /// right-hand side yields a single ε-production. Semantic actions in
+    /// braces are ignored if the configuration strips them. `line_number` is
+    /// only used in errors.
+    fn parse_production_line(
+        line: &str,
+        line_number: usize,
+        config: &GrammarConfig,
+    ) -> Result<Vec<Production>> {
+        let line = if config.strip_actions {
+            Self::strip_actions(line, line_number)?
+        } else {
+            line.to_string()
+        };
+        // Only the first arrow separates the sides, so `A -> ->` has the
+        // terminals - and > as its body
+        let parts: Vec<&str> = line.splitn(2, "->").collect();
         if parts.len() != 2 {
-            return Err(GrammarError::InvalidProduction(line.to_string()));
-        }
-
-        let lhs_str = parts[0].trim();
-        if lhs_str.is_empty() {
-            return Err(GrammarError::InvalidProduction(
-                "Empty left-hand side".to_string(),
-            ));
+            return Err(GrammarError::InvalidProduction {
+                line: line_number,
+                message: format!("'{}'", line.trim()),
+            });
         }
 
-        let lhs = Symbol::from_char(lhs_str.chars().next().unwrap());
+        let lhs = Self::parse_lhs(parts[0], line_number, config)?;
 
         let rhs_str = parts[1].trim();
+
+        // An empty or whitespace-only right-hand side ("A ->") is the
+        // textbook notation for A → ε
+        if rhs_str.is_empty() {
+            return Ok(vec![Production::new(lhs, vec![Symbol::Epsilon])]);
+        }
+
         let alternatives: Vec<&str> = rhs_str.split_whitespace().collect();
 
         let mut productions = Vec::new();
         for alt in alternatives {
-            let rhs = string_to_symbols(alt);
+            let rhs = config.body_to_symbols(alt);
             productions.push(Production::new(lhs, rhs));
         }
 
         Ok(productions)
     }
 
+    /// Parses the left-hand side of a rule, which must be a single nonterminal.
+    fn parse_lhs(lhs_str: &str, line: usize, config: &GrammarConfig) -> Result<Symbol> {
+        let lhs_str = lhs_str.trim();
+        let mut chars = lhs_str.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => Err(GrammarError::InvalidProduction {
+                line,
+                message: "Empty left-hand side".to_string(),
+            }),
+            (Some(c), None) if config.symbol_from_char(c).is_nonterminal() => {
+                Ok(config.symbol_from_char(c))
+            }
+            _ => Err(GrammarError::InvalidProduction {
+                line,
+                message: format!("Left-hand side must be a single nonterminal: {}", lhs_str),
+            }),
+        }
+    }
+
+    /// Parses a grammar whose rules are terminated by `;`.
+    ///
+    /// # Format
+    /// - Each rule is "A -> alpha | beta | gamma ;" and may span several lines
+    /// - Alternatives are separated by `|`; whitespace inside an alternative is
+    ///   ignored, so `E + T` is the body E+T
+    /// - An empty alternative (or `e`) is ε
+    ///
+    /// No count line is needed. As with [`parse`](Self::parse), the start
+    /// symbol is 'S'. Errors report the line on which the offending rule
+    /// starts.
+    pub fn parse_semicolon(text: &str) -> Result<Self> {
+        Self::parse_semicolon_with_config(text, GrammarConfig::default())
+    }
+
+    /// Parses a `;`-terminated grammar using the given configuration.
+    ///
+    /// With [`strip_actions`](GrammarConfig::strip_actions) set, semantic
+    /// actions in braces are ignored, and may span lines.
+    pub fn parse_semicolon_with_config(text: &str, config: GrammarConfig) -> Result<Self> {
+        let text = if config.strip_actions {
+            Self::strip_actions(strip_bom(text), 1)?
+        } else {
+            strip_bom(text).to_string()
+        };
+
+        // Each rule with the line of its first non-blank character
+        let mut rules: Vec<(usize, &str)> = Vec::new();
+        let mut line = 1;
+        for rule in text.split(';') {
+            let leading = &rule[..rule.len() - rule.trim_start().len()];
+            rules.push((line + leading.matches('\n').count(), rule));
+            line += rule.matches('\n').count();
+        }
+
+        let (trailing_line, trailing) = rules.pop().unwrap_or_default();
+        if !trailing.trim().is_empty() {
+            return Err(GrammarError::InvalidProduction {
+                line: trailing_line,
+                message: format!("Rule is missing its terminating ';': {}", trailing.trim()),
+            });
+        }
+
+        let mut all_productions = Vec::new();
+        for (line, rule) in rules.iter().filter(|(_, rule)| !rule.trim().is_empty()) {
+            let (lhs_str, rhs_str) =
+                rule.split_once("->")
+                    .ok_or_else(|| GrammarError::InvalidProduction {
+                        line: *line,
+                        message: format!("'{}'", rule.trim()),
+                    })?;
+
+            let lhs = Self::parse_lhs(lhs_str, *line, &config)?;
+
+            for alt in rhs_str.split('|') {
+                let body: String = alt.split_whitespace().collect();
+                let rhs = if body.is_empty() {
+                    vec![Symbol::Epsilon]
+                } else {
+                    config.body_to_symbols(&body)
+                };
+                all_productions.push(Production::new(lhs, rhs));
+            }
+        }
+
+        let mut grammar = Self::from_productions(all_productions)?;
+        grammar.config = config;
+        Ok(grammar)
+    }
+
+    /// Removes yacc-style `{ ... }` semantic actions from a production line.
+    ///
+    /// Each action (which may contain nested braces) is replaced by a space,
+    /// so an action between alternatives still separates them and braces never
+    /// become terminals. Line breaks inside actions are kept so that line
+    /// numbers stay valid; `first_line` is the line the text starts on.
+    fn strip_actions(line: &str, first_line: usize) -> Result<String> {
+        let mut stripped = String::with_capacity(line.len());
+        let mut depth = 0usize;
+        let (mut line_number, mut action_line) = (first_line, first_line);
+
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    if depth == 0 {
+                        action_line = line_number;
+                    }
+                    depth += 1;
+                }
+                '}' if depth == 0 => {
+                    return Err(GrammarError::InvalidProduction {
+                        line: line_number,
+                        message: format!("Unmatched '}}' in: {}", line),
+                    });
+                }
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        stripped.push(' ');
+                    }
+                }
+                '\n' => stripped.push(c),
+                _ if depth == 0 => stripped.push(c),
+                _ => {}
+            }
+            if c == '\n' {
+                line_number += 1;
+            }
+        }
+
+        if depth > 0 {
+            return Err(GrammarError::InvalidProduction {
+                line: action_line,
+                message: format!("Unterminated action in: {}", line),
+            });
+        }
+
+        Ok(stripped)
+    }
+
     /// Creates a grammar from a list of productions.
     fn from_productions(productions: Vec<Production>) -> Result<Self> {
+        // Parsed grammars always start at 'S'
+        Self::from_productions_with_start(productions, Symbol::Nonterminal('S'))
+    }
+
+    /// Creates a grammar from a list of productions with an explicit start symbol.
+    ///
+    /// The productions keep their order and the default configuration is
+    /// used. Fails with [`GrammarError::EmptyInput`] if there are none.
+    pub fn from_productions_with_start(
+        productions: Vec<Production>,
+        start_symbol: Symbol,
+    ) -> Result<Self> {
         if productions.is_empty() {
             return Err(GrammarError::EmptyInput);
         }
@@ -146,8 +566,9 @@ impl Grammar {
             .collect();
 
         // All nonterminals = LHS ∪ RHS nonterminals
-        let nonterminals: HashSet<Symbol> =
+        let mut nonterminals: HashSet<Symbol> =
             lhs_nonterminals.union(&rhs_nonterminals).copied().collect();
+        nonterminals.insert(start_symbol);
 
         // Terminals = non-nonterminal symbols from RHS (excluding epsilon and $)
         let terminals: HashSet<Symbol> = rhs_symbols
@@ -156,10 +577,8 @@ impl Grammar {
             .copied()
             .collect();
 
-        // Start symbol is always 'S'
-        let start_symbol = Symbol::Nonterminal('S');
-
-        // Build production map
+        // Build production map; pushing in order keeps each nonterminal's
+        // alternatives in source order
         let mut production_map: HashMap<Symbol, Vec<Production>> = HashMap::new();
         for prod in &productions {
             production_map
@@ -174,10 +593,33 @@ impl Grammar {
             terminals,
             start_symbol,
             production_map,
+            config: GrammarConfig::default(),
         })
     }
 
+    /// Creates a grammar from other productions, keeping this grammar's start
+    /// symbol and configuration.
+    ///
+    /// The derived indices (symbol sets and the per-nonterminal map) are
+    /// rebuilt from `productions`, which keep their order. This is how
+    /// transforms build their result. Fails with [`GrammarError::EmptyInput`]
+    /// if there are no productions.
+    pub fn clone_with_productions(&self, productions: Vec<Production>) -> Result<Self> {
+        let mut grammar = Self::from_productions_with_start(productions, self.start_symbol)?;
+        grammar.config = self.config.clone();
+        Ok(grammar)
+    }
+
+    /// Returns the configuration the grammar was parsed with.
+    pub fn config(&self) -> &GrammarConfig {
+        &self.config
+    }
+
     /// Returns all productions for a given nonterminal.
+    ///
+    /// The alternatives are in source order: the order they appear in
+    /// [`all_productions`](Self::all_productions), which is the order they
+    /// were read in. Output and conflict resolution rely on this.
     pub fn get_productions(&self, nt: Symbol) -> &[Production] {
         self.production_map
             .get(&nt)
@@ -190,6 +632,44 @@ impl Grammar {
         &self.productions
     }
 
+    /// Iterates over the productions as borrowed `(lhs, rhs)` pairs, in
+    /// production order.
+    ///
+    /// The body of an ε-production is `[ε]`, as in [`Production::rhs`].
+    pub fn alternatives_iter(&self) -> impl Iterator<Item = (Symbol, &[Symbol])> {
+        self.productions.iter().map(|p| (p.lhs, p.rhs.as_slice()))
+    }
+
+    /// Returns the productions with their numbers.
+    ///
+    /// Productions are numbered from 1 in grammar order, which is the order
+    /// they were read in; 0 is reserved for the augmented production S' → S
+    /// of the LR constructions. These are the numbers reductions are rendered
+    /// with, as in `r3`.
+    pub fn productions_indexed(&self) -> impl Iterator<Item = (usize, &Production)> {
+        self.productions
+            .iter()
+            .enumerate()
+            .map(|(index, production)| (index + 1, production))
+    }
+
+    /// Returns the number of a production, see
+    /// [`productions_indexed`](Self::productions_indexed).
+    pub fn production_index(&self, production: &Production) -> Option<usize> {
+        self.productions
+            .iter()
+            .position(|p| p == production)
+            .map(|index| index + 1)
+    }
+
+    /// Returns the production with the given number, the inverse of
+    /// [`production_index`](Self::production_index).
+    pub fn production(&self, number: usize) -> Option<&Production> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.productions.get(index))
+    }
+
     /// Returns all nonterminals in the grammar.
     pub fn nonterminals(&self) -> &HashSet<Symbol> {
         &self.nonterminals
@@ -200,10 +680,950 @@ impl Grammar {
         &self.terminals
     }
 
+    /// Returns every grammar symbol, terminals and nonterminals, in symbol
+    /// order (so all terminals come before the nonterminals).
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self
+            .terminals
+            .iter()
+            .chain(&self.nonterminals)
+            .copied()
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Returns every symbol reachable from `start` by following productions:
+    /// `start` itself and each terminal and nonterminal occurring in the
+    /// body of a reachable nonterminal. ε is never included.
+    ///
+    /// A terminal `start` reaches only itself.
+    pub fn reachable_from(&self, start: Symbol) -> HashSet<Symbol> {
+        let mut reachable = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(symbol) = queue.pop_front() {
+            let Some(productions) = self.production_map.get(&symbol) else {
+                continue;
+            };
+            for next in productions.iter().flat_map(|p| &p.rhs) {
+                if !next.is_epsilon() && reachable.insert(*next) {
+                    queue.push_back(*next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Exports the nonterminal dependency graph in Graphviz DOT format.
+    ///
+    /// There is one node per nonterminal, the start symbol first and drawn
+    /// with a double border, and an edge A → B whenever some production of A
+    /// references B. Edges are listed in symbol order.
+    pub fn dependency_dot(&self) -> String {
+        let mut nonterminals: Vec<Symbol> = self.nonterminals.iter().copied().collect();
+        nonterminals.sort_by_key(|nt| (*nt != self.start_symbol, *nt));
+
+        let mut edges: Vec<(Symbol, Symbol)> = self
+            .productions
+            .iter()
+            .flat_map(|p| {
+                p.rhs
+                    .iter()
+                    .filter(|s| s.is_nonterminal())
+                    .map(|s| (p.lhs, *s))
+            })
+            .collect();
+        edges.sort();
+        edges.dedup();
+
+        let quote = |symbol: Symbol| format!("\"{}\"", symbol.to_string().replace('"', "\\\""));
+        let mut output = String::from("digraph Dependencies {\n");
+        for nt in nonterminals {
+            if nt == self.start_symbol {
+                output.push_str(&format!("    {} [peripheries=2];\n", quote(nt)));
+            } else {
+                output.push_str(&format!("    {};\n", quote(nt)));
+            }
+        }
+        for (from, to) in edges {
+            output.push_str(&format!("    {} -> {};\n", quote(from), quote(to)));
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Returns the terminals in the order they first appear, scanning the
+    /// productions top to bottom and each body left to right.
+    pub fn terminals_in_order(&self) -> Vec<Symbol> {
+        let mut terminals = Vec::new();
+        for symbol in self.productions.iter().flat_map(|p| &p.rhs) {
+            if symbol.is_terminal() && !terminals.contains(symbol) {
+                terminals.push(*symbol);
+            }
+        }
+        terminals
+    }
+
+    /// Returns the terminals in the given column order.
+    pub(crate) fn terminals_ordered(&self, order: ColumnOrder) -> Vec<Symbol> {
+        match order {
+            ColumnOrder::Symbol => {
+                let mut terminals: Vec<Symbol> = self.terminals.iter().copied().collect();
+                terminals.sort();
+                terminals
+            }
+            ColumnOrder::FirstAppearance => self.terminals_in_order(),
+        }
+    }
+
     /// Returns the start symbol.
     pub fn start_symbol(&self) -> Symbol {
         self.start_symbol
     }
+
+    /// Makes another nonterminal the start symbol.
+    ///
+    /// Fails with [`GrammarError::InvalidFormat`] if the symbol is not a
+    /// nonterminal with productions; the grammar is then left unchanged.
+    pub fn set_start_symbol(&mut self, symbol: Symbol) -> Result<()> {
+        if !symbol.is_nonterminal() || !self.production_map.contains_key(&symbol) {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Start symbol must be a nonterminal with productions, got {}",
+                symbol
+            )));
+        }
+
+        // Rebuilt so that an old start without productions is dropped
+        let productions = std::mem::take(&mut self.productions);
+        let mut rebuilt = Self::from_productions_with_start(productions, symbol)?;
+        rebuilt.config = std::mem::take(&mut self.config);
+        *self = rebuilt;
+        Ok(())
+    }
+
+    /// Builds a grammar for the left quotient (Brzozowski derivative) of the
+    /// language by the terminal `t`, i.e. { w | tw ∈ L(G) }.
+    ///
+    /// # Algorithm
+    /// Every nonterminal A reached from the start gets a derivative nonterminal
+    /// Aₜ. For each production A → X₁X₂...Xₙ and each position k such that
+    /// X₁...Xₖ₋₁ are all nullable:
+    /// - If Xₖ = t, add Aₜ → Xₖ₊₁...Xₙ
+    /// - If Xₖ is a nonterminal B, add Aₜ → BₜXₖ₊₁...Xₙ
+    ///
+    /// The original productions are kept for the suffixes, and Sₜ becomes the
+    /// new start symbol.
+    pub fn derivative(&self, t: Symbol) -> Result<Grammar> {
+        if !t.is_terminal() {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Derivative is only defined for terminals, got {}",
+                t
+            )));
+        }
+
+        let nullable = nullable_symbols(self);
+        let mut names = NameGen::new(self);
+        let mut derived: HashMap<Symbol, Symbol> = HashMap::new();
+        let mut worklist = Vec::new();
+        let mut productions = self.productions.clone();
+
+        let mut derivative_of = |nt: Symbol, worklist: &mut Vec<Symbol>| -> Result<Symbol> {
+            if let Some(&existing) = derived.get(&nt) {
+                return Ok(existing);
+            }
+            let fresh = names.fresh()?;
+            derived.insert(nt, fresh);
+            worklist.push(nt);
+            Ok(fresh)
+        };
+
+        let start = derivative_of(self.start_symbol, &mut worklist)?;
+
+        while let Some(nt) = worklist.pop() {
+            let lhs = derivative_of(nt, &mut worklist)?;
+
+            for production in self.get_productions(nt) {
+                if production.is_epsilon() {
+                    continue;
+                }
+
+                for (k, &symbol) in production.rhs.iter().enumerate() {
+                    let rest = &production.rhs[k + 1..];
+
+                    if symbol == t {
+                        let rhs = if rest.is_empty() {
+                            vec![Symbol::Epsilon]
+                        } else {
+                            rest.to_vec()
+                        };
+                        productions.push(Production::new(lhs, rhs));
+                    } else if symbol.is_nonterminal() {
+                        let mut rhs = vec![derivative_of(symbol, &mut worklist)?];
+                        rhs.extend_from_slice(rest);
+                        productions.push(Production::new(lhs, rhs));
+                    }
+
+                    if !nullable.contains(&symbol) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut grammar = Self::from_productions_with_start(productions, start)?;
+        grammar.config = self.config.clone();
+        Ok(grammar)
+    }
+
+    /// Converts an input string to the grammar's terminals.
+    ///
+    /// Every character is one symbol, with the configured end marker read as
+    /// such. Fails with [`GrammarError::NonterminalInInput`] at the first
+    /// symbol that is not a terminal, and with
+    /// [`GrammarError::UnknownTerminal`] at the first terminal the grammar
+    /// does not use.
+    pub fn tokenize(&self, input: &str) -> Result<Vec<Symbol>> {
+        let symbols = self.config.string_to_symbols(input);
+        check_input(&symbols)?;
+        match symbols.iter().position(|s| !self.terminals.contains(s)) {
+            Some(position) => Err(GrammarError::UnknownTerminal {
+                position,
+                symbol: symbols[position].to_string(),
+            }),
+            None => Ok(symbols),
+        }
+    }
+
+    /// Checks whether the grammar accepts the input string.
+    ///
+    /// Uses the strongest parser available: LL(1) if the grammar is LL(1),
+    /// otherwise SLR(1), otherwise the general Earley parser, so any
+    /// context-free grammar (including ambiguous ones) gets a verdict.
+    pub fn accepts(&self, input: &str) -> Result<bool> {
+        Ok(self.accepts_batch(&[input])?[0])
+    }
+
+    /// Checks whether the grammar accepts each input string, in order.
+    ///
+    /// Like [`accepts`](Self::accepts), but the parser is chosen and built
+    /// once for the whole batch.
+    pub fn accepts_batch(&self, inputs: &[&str]) -> Result<Vec<bool>> {
+        let first_sets = compute_first_sets(self);
+        let follow_sets = compute_follow_sets(self, &first_sets);
+
+        if let Ok(parser) = LL1Parser::build(self.clone(), first_sets, follow_sets.clone()) {
+            return Ok(parser.parse_batch(inputs));
+        }
+        if let Ok(parser) = SLR1Parser::build(self.clone(), follow_sets) {
+            return Ok(parser.parse_batch(inputs));
+        }
+        let parser = EarleyParser::new(self.clone());
+        Ok(inputs.iter().map(|input| parser.parse(input)).collect())
+    }
+
+    /// Counts the distinct parse trees of the input, stopping at `max`.
+    ///
+    /// The trees are counted bottom-up over the Earley parse forest, see
+    /// [`SppfNode::count_trees`](crate::earley::SppfNode::count_trees), so any
+    /// grammar works and no tree is built; a rejected input has 0. A count
+    /// above 1 proves the grammar ambiguous. As in
+    /// [`EarleyParser::parse_forest`], cyclic derivations are not counted.
+    pub fn num_derivations(&self, input: &str, max: usize) -> usize {
+        EarleyParser::new(self.clone())
+            .parse_forest(input)
+            .map_or(0, |forest| forest.count_trees(max))
+    }
+
+    /// Reports every pair of alternatives of `nt` that start with the same
+    /// symbols, together with the length of their common prefix.
+    ///
+    /// These are the FIRST/FIRST clashes that left factoring removes. Pairs are
+    /// listed in production order.
+    pub fn alternatives_sharing_prefix(&self, nt: Symbol) -> Vec<(Production, Production, usize)> {
+        let alternatives = self.get_productions(nt);
+        let mut pairs = Vec::new();
+
+        for (i, first) in alternatives.iter().enumerate() {
+            for second in &alternatives[i + 1..] {
+                let length = first
+                    .rhs
+                    .iter()
+                    .zip(&second.rhs)
+                    .take_while(|(a, b)| a == b && !a.is_epsilon())
+                    .count();
+                if length > 0 {
+                    pairs.push((first.clone(), second.clone(), length));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// Enumerates the sentential forms derivable from the start symbol in at
+    /// most `max_steps` leftmost derivation steps.
+    ///
+    /// Forms still containing nonterminals are included, starting with the
+    /// start symbol itself; ε is represented by the empty form. Forms are
+    /// listed breadth-first (by derivation length) without duplicates. The
+    /// number of forms can grow exponentially with `max_steps`, so keep it small.
+    pub fn sentential_forms(&self, max_steps: usize) -> Vec<Vec<Symbol>> {
+        let mut forms = vec![vec![self.start_symbol]];
+        let mut seen: HashSet<Vec<Symbol>> = forms.iter().cloned().collect();
+        let mut frontier = forms.clone();
+
+        for _ in 0..max_steps {
+            let mut next = Vec::new();
+            for form in &frontier {
+                let Some(position) = form.iter().position(|s| s.is_nonterminal()) else {
+                    continue;
+                };
+                for production in self.get_productions(form[position]) {
+                    let mut derived = form[..position].to_vec();
+                    if !production.is_epsilon() {
+                        derived.extend(&production.rhs);
+                    }
+                    derived.extend(&form[position + 1..]);
+                    if seen.insert(derived.clone()) {
+                        next.push(derived);
+                    }
+                }
+            }
+            forms.extend(next.iter().cloned());
+            frontier = next;
+        }
+
+        forms
+    }
+
+    /// Combines this grammar with another one under the given start symbol.
+    ///
+    /// Productions are unioned without duplicates, so a fragment can define
+    /// nonterminals that this grammar only references. A nonterminal defined
+    /// in both grammars must have the same alternatives in each; otherwise
+    /// merging would silently change its meaning and an error is returned.
+    /// The merged grammar keeps this grammar's configuration.
+    pub fn merge(&self, other: &Grammar, start: Symbol) -> Result<Grammar> {
+        if !start.is_nonterminal() {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Start symbol must be a nonterminal, got {}",
+                start
+            )));
+        }
+
+        for nt in self.production_map.keys() {
+            let Some(theirs) = other.production_map.get(nt) else {
+                continue;
+            };
+            let ours: HashSet<&Production> = self.production_map[nt].iter().collect();
+            let theirs: HashSet<&Production> = theirs.iter().collect();
+            if ours != theirs {
+                return Err(GrammarError::InvalidFormat(format!(
+                    "Nonterminal {} is defined differently in the merged grammars",
+                    nt
+                )));
+            }
+        }
+
+        let mut productions = self.productions.clone();
+        for production in &other.productions {
+            if !productions.contains(production) {
+                productions.push(production.clone());
+            }
+        }
+
+        let mut merged = Self::from_productions_with_start(productions, start)?;
+        merged.config = self.config.clone();
+        Ok(merged)
+    }
+
+    /// Returns the grammar with a single-production edit applied.
+    ///
+    /// Adding a production that is already present leaves the grammar
+    /// unchanged; removing or replacing one that is absent is an error. The
+    /// start symbol and configuration are kept.
+    pub fn apply_change(&self, change: &GrammarChange) -> Result<Grammar> {
+        let mut productions = self.productions.clone();
+        let missing = |production: &Production| {
+            GrammarError::InvalidFormat(format!("Production {} is not in the grammar", production))
+        };
+
+        match change {
+            GrammarChange::Add(production) => {
+                if !productions.contains(production) {
+                    productions.push(production.clone());
+                }
+            }
+            GrammarChange::Remove(production) => {
+                let index = productions
+                    .iter()
+                    .position(|p| p == production)
+                    .ok_or_else(|| missing(production))?;
+                productions.remove(index);
+            }
+            GrammarChange::Replace { old, new } => {
+                let index = productions
+                    .iter()
+                    .position(|p| p == old)
+                    .ok_or_else(|| missing(old))?;
+                if old != new && productions.contains(new) {
+                    productions.remove(index);
+                } else {
+                    productions[index] = new.clone();
+                }
+            }
+        }
+
+        self.clone_with_productions(productions)
+    }
+
+    /// Writes the grammar in the line format read by [`parse`](Self::parse),
+    /// or by [`parse_with_config`](Self::parse_with_config) with this
+    /// grammar's configuration.
+    ///
+    /// There is one line per nonterminal with productions, start symbol
+    /// first, listing its alternatives separated by spaces and ε as `e`.
+    /// Fails with [`GrammarError::InvalidFormat`] when the grammar cannot be
+    /// spelled in that format: a start symbol other than `S`, or a symbol such
+    /// as the terminal `e` or an uppercase terminal that would be read back
+    /// differently.
+    pub fn to_input_format(&self) -> Result<String> {
+        if self.start_symbol != Symbol::Nonterminal('S') {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Start symbol must be S to be written, got {}",
+                self.start_symbol
+            )));
+        }
+
+        let nonterminals = self.defined_nonterminals();
+        let mut output = format!("{}\n", nonterminals.len());
+        for nt in nonterminals {
+            self.check_writable(nt)?;
+            let mut alternatives = Vec::new();
+            for production in &self.production_map[&nt] {
+                if production.is_epsilon() {
+                    alternatives.push("e".to_string());
+                    continue;
+                }
+                let mut body = String::new();
+                for symbol in &production.rhs {
+                    if *symbol == Symbol::Error {
+                        body.push_str("\\E");
+                        continue;
+                    }
+                    self.check_writable(*symbol)?;
+                    body.push_str(&symbol.to_string());
+                }
+                alternatives.push(body);
+            }
+            output.push_str(&format!("{} -> {}\n", nt, alternatives.join(" ")));
+        }
+        Ok(output)
+    }
+
+    /// Checks that a symbol reads back as itself when written as a character.
+    fn check_writable(&self, symbol: Symbol) -> Result<()> {
+        let writable = match symbol.as_char() {
+            // Spaces, tabs and newlines are written as escape sequences
+            Some('\t' | '\n' | ' ') => symbol.is_terminal(),
+            // Braces would be read back as an action when actions are stripped
+            Some('{' | '}') if self.config.strip_actions => false,
+            Some(c) => !c.is_whitespace() && self.config.symbol_from_char(c) == symbol,
+            None => false,
+        };
+        if writable {
+            Ok(())
+        } else {
+            Err(GrammarError::InvalidFormat(format!(
+                "Symbol {:?} cannot be written in the input format",
+                symbol
+            )))
+        }
+    }
+
+    /// Renders the grammar with one line per nonterminal and its alternatives
+    /// joined by ` | `, such as `S → aSb | ε`.
+    ///
+    /// The start symbol comes first, followed by the other nonterminals in
+    /// symbol order.
+    pub fn to_pretty_string(&self) -> String {
+        let mut output = String::new();
+        for nt in self.defined_nonterminals() {
+            let alternatives: Vec<String> = self.production_map[&nt]
+                .iter()
+                .map(|production| {
+                    if production.is_epsilon() {
+                        "ε".to_string()
+                    } else {
+                        symbols_to_string(&production.rhs)
+                    }
+                })
+                .collect();
+            output.push_str(&format!("{} → {}\n", nt, alternatives.join(" | ")));
+        }
+        output
+    }
+
+    /// Returns the nonterminals with productions, start symbol first and the
+    /// rest in symbol order.
+    fn defined_nonterminals(&self) -> Vec<Symbol> {
+        let mut nonterminals: Vec<Symbol> = self.production_map.keys().copied().collect();
+        nonterminals.sort_by_key(|nt| (*nt != self.start_symbol, *nt));
+        nonterminals
+    }
+
+    /// Renders the grammar as a LaTeX `align*` environment, one line per
+    /// nonterminal with its alternatives joined by `\mid`.
+    ///
+    /// The start symbol comes first, followed by the other nonterminals in
+    /// symbol order; an ε-alternative is written `\varepsilon`.
+    pub fn to_latex(&self) -> String {
+        let lines: Vec<String> = self
+            .defined_nonterminals()
+            .iter()
+            .map(|nt| {
+                let alternatives: Vec<String> = self.production_map[nt]
+                    .iter()
+                    .map(|production| {
+                        production
+                            .rhs
+                            .iter()
+                            .map(|s| self.config.symbol_to_latex(*s))
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    })
+                    .collect();
+                format!(
+                    "{} &\\to {}",
+                    self.config.symbol_to_latex(*nt),
+                    alternatives.join(" \\mid ")
+                )
+            })
+            .collect();
+
+        format!(
+            "\\begin{{align*}}\n{}\n\\end{{align*}}\n",
+            lines.join(" \\\\\n")
+        )
+    }
+
+    /// Renders the grammar in ANTLR 4 syntax, as a combined grammar named
+    /// `Grammar` (so it belongs in `Grammar.g4`).
+    ///
+    /// ANTLR parser rules must start with a lowercase letter and lexer rules
+    /// with an uppercase one, so symbols are mapped as follows:
+    /// - a nonterminal `A`-`Z` becomes the parser rule `a`-`z`; any other
+    ///   nonterminal becomes `nt_` followed by its code point in hex
+    /// - a terminal becomes a quoted literal such as `'a'`, which ANTLR turns
+    ///   into an implicit lexer token; quotes, backslashes and control
+    ///   characters are escaped, as in `'\n'` or `'\u{7F}'`
+    /// - an ε-alternative becomes an empty alternative
+    ///
+    /// An extra `parse : s EOF ;` rule, first in the output, matches the whole
+    /// input like the parsers here do. ANTLR only handles direct left
+    /// recursion, so indirectly left-recursive grammars need
+    /// [`GrammarPipeline::eliminate_left_recursion`] first.
+    ///
+    /// Fails with [`GrammarError::InvalidFormat`] if a production uses the
+    /// `error` token, which ANTLR has no counterpart for since it recovers
+    /// from errors on its own, or a [`Symbol::Special`] marker.
+    pub fn to_antlr(&self) -> Result<String> {
+        let mut output = format!(
+            "grammar Grammar;\n\nparse : {} EOF ;\n",
+            antlr_symbol(self.start_symbol)?
+        );
+        for nt in self.defined_nonterminals() {
+            let mut alternatives = Vec::new();
+            for production in &self.production_map[&nt] {
+                if production.is_epsilon() {
+                    alternatives.push("/* ε */".to_string());
+                } else {
+                    let symbols = production
+                        .rhs
+                        .iter()
+                        .map(|s| antlr_symbol(*s))
+                        .collect::<Result<Vec<String>>>()?;
+                    alternatives.push(symbols.join(" "));
+                }
+            }
+            output.push_str(&format!(
+                "\n{} : {} ;\n",
+                antlr_symbol(nt)?,
+                alternatives.join(" | ")
+            ));
+        }
+        Ok(output)
+    }
+
+    /// Renders the grammar in BNF: one `<A> ::= ...` rule per nonterminal with
+    /// its alternatives joined by ` | `.
+    ///
+    /// Nonterminals are written in angle brackets and terminals in double
+    /// quotes (single quotes for `"` itself); an ε-alternative is `""`. The
+    /// start symbol comes first, followed by the other nonterminals in symbol
+    /// order.
+    pub fn to_bnf(&self) -> String {
+        self.defined_nonterminals()
+            .iter()
+            .map(|nt| {
+                let alternatives: Vec<String> = self.production_map[nt]
+                    .iter()
+                    .map(|production| bnf_body(&production.rhs, &HashMap::new()))
+                    .collect();
+                format!("{} ::= {}\n", bnf_symbol(*nt), alternatives.join(" | "))
+            })
+            .collect()
+    }
+
+    /// Renders the grammar in EBNF, folding simple repetition nonterminals
+    /// back into the places that use them.
+    ///
+    /// The notation is that of [`to_bnf`](Self::to_bnf). A nonterminal `T`
+    /// other than the start symbol is folded when its only productions are
+    /// - `T → αT | ε`, written `α*`, the tail left by left-recursion
+    ///   elimination
+    /// - `T → αT | α`, written `α+`
+    /// - `T → α | ε`, written `α?`
+    ///
+    /// where α is nonempty, does not mention `T` and mentions no other folded
+    /// nonterminal. Each use of `T` is replaced and its rule dropped; rules
+    /// that do not match are kept as in BNF.
+    pub fn to_ebnf(&self) -> String {
+        let candidates: HashMap<Symbol, (&[Symbol], char)> = self
+            .defined_nonterminals()
+            .into_iter()
+            .filter(|nt| *nt != self.start_symbol)
+            .filter_map(|nt| Some((nt, self.repetition(nt)?)))
+            .collect();
+        let folded: HashMap<Symbol, (&[Symbol], char)> = candidates
+            .iter()
+            .filter(|(nt, (body, _))| {
+                body.iter().all(|s| !candidates.contains_key(s))
+                    && self
+                        .productions
+                        .iter()
+                        .any(|p| p.lhs != **nt && p.rhs.contains(nt))
+            })
+            .map(|(nt, repetition)| (*nt, *repetition))
+            .collect();
+
+        self.defined_nonterminals()
+            .iter()
+            .filter(|nt| !folded.contains_key(nt))
+            .map(|nt| {
+                let alternatives: Vec<String> = self.production_map[nt]
+                    .iter()
+                    .map(|production| bnf_body(&production.rhs, &folded))
+                    .collect();
+                format!("{} ::= {}\n", bnf_symbol(*nt), alternatives.join(" | "))
+            })
+            .collect()
+    }
+
+    /// Matches the productions of `nt` against the repetition shapes folded
+    /// by [`to_ebnf`](Self::to_ebnf), returning the repeated body and its
+    /// operator.
+    fn repetition(&self, nt: Symbol) -> Option<(&[Symbol], char)> {
+        let [first, second] = self.get_productions(nt) else {
+            return None;
+        };
+        let (longer, shorter) = if first.rhs.len() >= second.rhs.len() {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        let (body, operator) = match longer.rhs.split_last() {
+            Some((last, body)) if *last == nt && shorter.is_epsilon() => (body, '*'),
+            Some((last, body)) if *last == nt && shorter.rhs == body => (body, '+'),
+            _ if shorter.is_epsilon() => (longer.rhs.as_slice(), '?'),
+            _ => return None,
+        };
+        (!body.is_empty() && !body.contains(&nt)).then_some((body, operator))
+    }
+
+    /// Renames the nonterminals to a canonical scheme, so that grammars
+    /// differing only in their nonterminal names become identical.
+    ///
+    /// The start symbol becomes `S`; the other nonterminals are named `A`,
+    /// `B`, ... (skipping `S`, then the Greek capitals, see [`NameGen`]) in
+    /// the order a breadth-first search from the start symbol discovers them,
+    /// scanning each nonterminal's alternatives in source order. Nonterminals
+    /// unreachable from the start are named afterwards, in order of first
+    /// appearance. Productions keep their order, so grammars listing the same
+    /// alternatives in different orders are not identified.
+    pub fn rename_to_canonical(&self) -> Result<Grammar> {
+        let canonical_start = Symbol::Nonterminal('S');
+        let mut names = NameGen::default();
+        names.reserve(canonical_start);
+        let mut renamed: HashMap<Symbol, Symbol> = HashMap::new();
+
+        let roots =
+            std::iter::once(self.start_symbol).chain(self.productions.iter().map(|p| p.lhs));
+        for root in roots {
+            if renamed.contains_key(&root) {
+                continue;
+            }
+            let name = if root == self.start_symbol {
+                canonical_start
+            } else {
+                names.fresh()?
+            };
+            renamed.insert(root, name);
+
+            let mut queue = VecDeque::from([root]);
+            while let Some(nt) = queue.pop_front() {
+                for production in self.get_productions(nt) {
+                    for symbol in &production.rhs {
+                        if symbol.is_nonterminal() && !renamed.contains_key(symbol) {
+                            renamed.insert(*symbol, names.fresh()?);
+                            queue.push_back(*symbol);
+                        }
+                    }
+                }
+            }
+        }
+
+        let rename = |symbol: &Symbol| *renamed.get(symbol).unwrap_or(symbol);
+        let productions = self
+            .productions
+            .iter()
+            .map(|p| Production::new(rename(&p.lhs), p.rhs.iter().map(rename).collect()))
+            .collect();
+        let mut grammar = Self::from_productions_with_start(productions, canonical_start)?;
+        grammar.config = self.config.clone();
+        Ok(grammar)
+    }
+
+    /// Inlines a sub-grammar: the productions of `nt` are replaced by those of
+    /// `sub`, with `sub`'s start symbol renamed to `nt`.
+    ///
+    /// The other nonterminals of `sub` keep their names unless this grammar
+    /// already uses them, in which case they get fresh ones from [`NameGen`].
+    /// Nonterminals only reachable through the old productions of `nt` are
+    /// kept; [`GrammarPipeline::remove_useless`] drops them. The start symbol
+    /// and configuration are those of this grammar.
+    pub fn substitute(&self, nt: Symbol, sub: &Grammar) -> Result<Grammar> {
+        if !self.nonterminals.contains(&nt) {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Cannot substitute {}: it is not a nonterminal of the grammar",
+                nt
+            )));
+        }
+
+        let mut names = NameGen::new(self);
+        for symbol in sub.nonterminals() {
+            names.reserve(*symbol);
+        }
+        let mut renamed: HashMap<Symbol, Symbol> = HashMap::from([(sub.start_symbol, nt)]);
+        let mut collisions: Vec<Symbol> = sub
+            .nonterminals()
+            .iter()
+            .filter(|s| **s != sub.start_symbol && self.nonterminals.contains(s))
+            .copied()
+            .collect();
+        collisions.sort();
+        for symbol in collisions {
+            renamed.insert(symbol, names.fresh()?);
+        }
+
+        let rename = |symbol: &Symbol| *renamed.get(symbol).unwrap_or(symbol);
+        let productions = self
+            .productions
+            .iter()
+            .filter(|p| p.lhs != nt)
+            .cloned()
+            .chain(
+                sub.productions
+                    .iter()
+                    .map(|p| Production::new(rename(&p.lhs), p.rhs.iter().map(rename).collect())),
+            )
+            .collect();
+        self.clone_with_productions(productions)
+    }
+
+    /// Builds the sub-grammar over a smaller alphabet.
+    ///
+    /// Drops every production using a terminal outside `allowed`, then removes
+    /// the symbols left useless. Fails with [`GrammarError::EmptyInput`] when
+    /// the start symbol derives no string over `allowed`.
+    pub fn restrict_to_terminals(&self, allowed: &HashSet<Symbol>) -> Result<Grammar> {
+        let productions: Vec<Production> = self
+            .productions
+            .iter()
+            .filter(|p| {
+                p.rhs
+                    .iter()
+                    .all(|s| !s.is_terminal() || allowed.contains(s))
+            })
+            .cloned()
+            .collect();
+        if productions.is_empty() {
+            return Err(GrammarError::EmptyInput);
+        }
+
+        remove_useless_symbols(&self.clone_with_productions(productions)?)
+    }
+
+    /// Checks whether the grammar generates a finite language.
+    ///
+    /// # Algorithm
+    /// ε- and unit productions are removed, then useless symbols, so every
+    /// remaining production either is a single terminal or has a body of two
+    /// or more symbols that each derive a non-empty string. The language is
+    /// infinite exactly when some nonterminal of that reduced grammar derives
+    /// a string containing itself, i.e. when its dependency graph has a cycle.
+    /// A grammar whose language is empty or just {ε} is finite.
+    pub fn language_is_finite(&self) -> bool {
+        let Ok(reduced) = GrammarPipeline::new(self.clone())
+            .remove_epsilon()
+            .remove_unit()
+            .remove_useless()
+            .build()
+        else {
+            return true;
+        };
+
+        // A nonterminal is recursive if it reaches itself through bodies
+        let mut edges: HashMap<Symbol, HashSet<Symbol>> = HashMap::new();
+        for production in reduced.all_productions() {
+            edges
+                .entry(production.lhs)
+                .or_default()
+                .extend(production.rhs.iter().filter(|s| s.is_nonterminal()));
+        }
+
+        reduced.nonterminals().iter().all(|&nt| {
+            let mut seen = HashSet::new();
+            let mut worklist: Vec<Symbol> = edges.get(&nt).into_iter().flatten().copied().collect();
+            while let Some(next) = worklist.pop() {
+                if next == nt {
+                    return false;
+                }
+                if seen.insert(next) {
+                    worklist.extend(edges.get(&next).into_iter().flatten().copied());
+                }
+            }
+            true
+        })
+    }
+
+    /// Runs every structural check and returns all violations at once.
+    ///
+    /// A well-formed grammar has productions for its start symbol and for
+    /// every nonterminal it references, uses ε only as a whole body, and
+    /// never mentions the end marker in a body. Each violation is a
+    /// [`GrammarError::InvalidFormat`]; they are reported in that order, with
+    /// productions in source order.
+    pub fn validate(&self) -> std::result::Result<(), Vec<GrammarError>> {
+        let mut errors = Vec::new();
+
+        if !self.production_map.contains_key(&self.start_symbol) {
+            errors.push(GrammarError::InvalidFormat(format!(
+                "Start symbol {} has no productions",
+                self.start_symbol
+            )));
+        }
+
+        let mut undefined: Vec<Symbol> = self
+            .nonterminals
+            .iter()
+            .filter(|nt| **nt != self.start_symbol && !self.production_map.contains_key(nt))
+            .copied()
+            .collect();
+        undefined.sort();
+        for nt in undefined {
+            errors.push(GrammarError::InvalidFormat(format!(
+                "Nonterminal {} is used but has no productions",
+                nt
+            )));
+        }
+
+        for production in &self.productions {
+            if production.rhs.len() > 1 && production.rhs.contains(&Symbol::Epsilon) {
+                errors.push(GrammarError::InvalidFormat(format!(
+                    "ε must be the whole body, not part of {}",
+                    production
+                )));
+            }
+            if production.rhs.contains(&Symbol::EndMarker) {
+                errors.push(GrammarError::InvalidFormat(format!(
+                    "The end marker cannot appear in {}",
+                    production
+                )));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks whether the grammar is in Chomsky normal form.
+    ///
+    /// Every production must be A → BC or A → a, except that S → ε is allowed
+    /// when the start symbol S appears on no right-hand side.
+    pub fn is_cnf(&self) -> bool {
+        self.productions
+            .iter()
+            .all(|production| match production.rhs.as_slice() {
+                [b, c] => b.is_nonterminal() && c.is_nonterminal(),
+                [a] if a.is_terminal() => true,
+                _ => self.is_start_epsilon(production),
+            })
+    }
+
+    /// Checks whether the grammar is in Greibach normal form.
+    ///
+    /// Every production must be A → aα with α a (possibly empty) string of
+    /// nonterminals, except that S → ε is allowed when the start symbol S
+    /// appears on no right-hand side.
+    pub fn is_gnf(&self) -> bool {
+        self.productions
+            .iter()
+            .all(|production| match production.rhs.split_first() {
+                Some((a, alpha)) if a.is_terminal() => alpha.iter().all(|s| s.is_nonterminal()),
+                _ => self.is_start_epsilon(production),
+            })
+    }
+
+    /// Returns the first production with two adjacent nonterminals on its
+    /// right-hand side, such as A → BC or A → aBCb.
+    ///
+    /// Such a production keeps the grammar from being an operator grammar,
+    /// so this is why operator-precedence (Floyd) parsing does not apply.
+    pub fn has_adjacent_nonterminals(&self) -> Option<Production> {
+        self.productions
+            .iter()
+            .find(|production| {
+                production
+                    .rhs
+                    .windows(2)
+                    .any(|pair| pair[0].is_nonterminal() && pair[1].is_nonterminal())
+            })
+            .cloned()
+    }
+
+    /// Checks if a production is S → ε with the start symbol S never used on
+    /// a right-hand side, the one ε-production the normal forms permit.
+    fn is_start_epsilon(&self, production: &Production) -> bool {
+        production.is_epsilon()
+            && production.lhs == self.start_symbol
+            && self
+                .productions
+                .iter()
+                .all(|p| !p.rhs.contains(&self.start_symbol))
+    }
+
+    /// Returns how many alternatives each nonterminal has.
+    pub fn productions_count_by_nonterminal(&self) -> HashMap<Symbol, usize> {
+        self.production_map
+            .iter()
+            .map(|(nt, prods)| (*nt, prods.len()))
+            .collect()
+    }
 }
 
 impl fmt::Display for Grammar {
@@ -214,3 +1634,179 @@ impl fmt::Display for Grammar {
         Ok(())
     }
 }
+
+/// Renders a symbol in BNF notation, see [`Grammar::to_bnf`].
+fn bnf_symbol(symbol: Symbol) -> String {
+    match symbol {
+        Symbol::Nonterminal(c) => format!("<{}>", c),
+        Symbol::Terminal('"') => "'\"'".to_string(),
+        Symbol::Terminal(c) => format!("\"{}\"", c),
+        other => other.to_string(),
+    }
+}
+
+/// Renders a production body in BNF notation, writing each nonterminal of
+/// `folded` as its repeated body and operator.
+fn bnf_body(rhs: &[Symbol], folded: &HashMap<Symbol, (&[Symbol], char)>) -> String {
+    if rhs == [Symbol::Epsilon] {
+        return "\"\"".to_string();
+    }
+    rhs.iter()
+        .map(|symbol| match folded.get(symbol) {
+            Some(([single], operator)) => format!("{}{}", bnf_symbol(*single), operator),
+            Some((body, operator)) => {
+                format!("( {} ){}", bnf_body(body, &HashMap::new()), operator)
+            }
+            None => bnf_symbol(*symbol),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Renders a symbol as an ANTLR rule reference or literal, see
+/// [`Grammar::to_antlr`].
+fn antlr_symbol(symbol: Symbol) -> Result<String> {
+    Ok(match symbol {
+        Symbol::Nonterminal(c) if c.is_ascii_uppercase() => c.to_ascii_lowercase().to_string(),
+        Symbol::Nonterminal(c) => format!("nt_{:x}", c as u32),
+        Symbol::Terminal('\'') => "'\\''".to_string(),
+        Symbol::Terminal('\\') => "'\\\\'".to_string(),
+        Symbol::Terminal('\n') => "'\\n'".to_string(),
+        Symbol::Terminal('\r') => "'\\r'".to_string(),
+        Symbol::Terminal('\t') => "'\\t'".to_string(),
+        Symbol::Terminal(c) if c.is_control() => format!("'\\u{{{:X}}}'", c as u32),
+        Symbol::Terminal(c) => format!("'{}'", c),
+        Symbol::EndMarker => "EOF".to_string(),
+        Symbol::Epsilon => String::new(),
+        Symbol::Error | Symbol::Special(_) => {
+            return Err(GrammarError::InvalidFormat(format!(
+                "Symbol {} cannot be written in ANTLR syntax",
+                symbol
+            )));
+        }
+    })
+}
+
+/// Returns the first LL(1) conflict of a grammar, or `None` if it is LL(1).
+///
+/// This is a lighter alternative to [`LL1Parser::build`](crate::ll1::LL1Parser::build)
+/// when only a yes/no answer plus one example conflict is needed: it runs the
+/// same table construction, stopping at the first conflicting cell, but never
+/// assembles a parser.
+pub fn first_ll1_conflict(
+    grammar: &Grammar,
+    first_sets: &FirstSets,
+    follow_sets: &FollowSets,
+) -> Option<GrammarError> {
+    ll1::build_table(grammar, first_sets, follow_sets).err()
+}
+
+/// Checks whether two grammars accept the same nonempty inputs of at most
+/// `max_len` terminals.
+///
+/// The empty input is ignored, so this is the guarantee of
+/// [`remove_epsilon_productions`](crate::transform::remove_epsilon_productions):
+/// the language is preserved except possibly for ε. Like
+/// [`equivalent_up_to`](crate::analysis::equivalent_up_to) the check is
+/// bounded and exhaustive.
+pub fn languages_equal_modulo_empty(a: &Grammar, b: &Grammar, max_len: usize) -> bool {
+    shortest_difference_from(a, b, 1, max_len).is_none()
+}
+
+/// Checks whether k symbols of lookahead distinguish every pair of
+/// alternatives of the grammar.
+///
+/// For each nonterminal A with alternatives α and β the sets
+/// FIRST_k(α FOLLOW_k(A)) and FIRST_k(β FOLLOW_k(A)) must be disjoint; this is
+/// the strong LL(k) condition. It is a heuristic for whether raising the
+/// lookahead would make an LL(1)-failing grammar parseable top-down.
+pub fn is_ll_k_candidate(grammar: &Grammar, k: usize) -> bool {
+    let first_sets = compute_first_sets_k(grammar, k);
+    let follow_sets = compute_follow_sets_k(grammar, &first_sets, k);
+
+    grammar.nonterminals().iter().all(|nt| {
+        let follow = follow_sets.get(nt).cloned().unwrap_or_default();
+        let lookaheads: Vec<HashSet<Vec<Symbol>>> = grammar
+            .get_productions(*nt)
+            .iter()
+            .map(|production| {
+                let first = first_k_of_string(&first_sets, &production.rhs, k);
+                concat_k(&first, &follow, k)
+            })
+            .collect();
+
+        lookaheads.iter().enumerate().all(|(i, first)| {
+            lookaheads[i + 1..]
+                .iter()
+                .all(|second| first.is_disjoint(second))
+        })
+    })
+}
+
+/// A soft warning about grammar source text.
+///
+/// Lints never make a grammar invalid; they point at constructs that are
+/// legal but often a mistake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// A nonterminal's alternatives are declared on non-contiguous lines.
+    SplitDeclaration {
+        nonterminal: Symbol,
+        /// The 1-based production lines declaring the nonterminal, numbered
+        /// as in [`GrammarError::InvalidProduction`]: the count line is not
+        /// counted, so the first production line is line 1
+        lines: Vec<usize>,
+    },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::SplitDeclaration { nonterminal, lines } => {
+                let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                write!(
+                    f,
+                    "alternatives of {} are split across lines {}",
+                    nonterminal,
+                    lines.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Removes a leading UTF-8 byte order mark, which unlike `\r` is not
+/// whitespace and so survives trimming.
+fn strip_bom(text: &str) -> &str {
+    text.strip_prefix('\u{feff}').unwrap_or(text)
+}
+
+/// Lints grammar source in the format read by [`Grammar::parse`].
+///
+/// Line numbers count production lines from 1, leaving out the count line,
+/// like those of [`GrammarError::InvalidProduction`]. Lints are returned in
+/// the order of the nonterminal's first declaration. The lines must form a
+/// valid grammar.
+pub fn lint(lines: &[String]) -> Result<Vec<Lint>> {
+    let grammar = Grammar::parse(lines)?;
+    let n = Grammar::parse_count(&lines[0])?;
+
+    // Production lines declaring each nonterminal, in order of first
+    // declaration
+    let mut declarations: Vec<(Symbol, Vec<usize>)> = Vec::new();
+    for (index, line) in lines[1..=n].iter().enumerate() {
+        let line_number = index + 1;
+        let productions = Grammar::parse_production_line(line, line_number, grammar.config())?;
+        let lhs = productions[0].lhs;
+        match declarations.iter_mut().find(|(nt, _)| *nt == lhs) {
+            Some((_, declared)) => declared.push(line_number),
+            None => declarations.push((lhs, vec![line_number])),
+        }
+    }
+
+    Ok(declarations
+        .into_iter()
+        .filter(|(_, declared)| declared.windows(2).any(|pair| pair[1] != pair[0] + 1))
+        .map(|(nonterminal, lines)| Lint::SplitDeclaration { nonterminal, lines })
+        .collect())
+}
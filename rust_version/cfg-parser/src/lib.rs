@@ -2,17 +2,23 @@
 //!
 //! A Rust implementation of LL(1) and SLR(1) parsers for context-free grammars.
 
+pub mod analysis;
 pub mod cli;
+pub mod earley;
 pub mod error;
 pub mod first_follow;
 pub mod grammar;
 pub mod ll1;
 pub mod slr1;
 pub mod symbol;
+pub mod trace;
+pub mod transform;
 
 // Re-export commonly used types
+pub use earley::EarleyParser;
 pub use error::{GrammarError, Result};
-pub use grammar::{Grammar, Production};
+pub use grammar::{ColumnOrder, Grammar, GrammarChange, GrammarConfig, Lint, Production};
 pub use ll1::LL1Parser;
 pub use slr1::SLR1Parser;
 pub use symbol::Symbol;
+pub use trace::{ParseTrace, Rejection, TraceStep};
@@ -3,17 +3,24 @@
 //! This module implements a top-down LL(1) predictive parser using a parse table.
 
 use crate::error::{GrammarError, Result};
-use crate::first_follow::{first_of_string, FirstSets, FollowSets};
-use crate::grammar::{Grammar, Production};
-use crate::symbol::{string_to_symbols, Symbol};
+use crate::first_follow::{
+    compute_first_sets, compute_follow_sets, first_of_string, FirstSets, FollowSets,
+};
+use crate::grammar::{ColumnOrder, Grammar, Production};
+use crate::symbol::Symbol;
+use crate::trace::{check_input, render_markdown, render_table, ParseTrace, Rejection};
+use crate::transform::GrammarPipeline;
 use std::collections::HashMap;
 
+/// LL(1) parse table: M[Nonterminal, Terminal/EndMarker] = Production
+type ParseTable = HashMap<(Symbol, Symbol), Production>;
+
 /// LL(1) predictive parser.
 #[derive(Debug)]
 pub struct LL1Parser {
     grammar: Grammar,
     /// Parse table: M[Nonterminal, Terminal/EndMarker] = Production
-    table: HashMap<(Symbol, Symbol), Production>,
+    table: ParseTable,
     first_sets: FirstSets,
     follow_sets: FollowSets,
 }
@@ -28,54 +35,51 @@ impl LL1Parser {
     ///
     /// If any cell has multiple entries, the grammar is not LL(1).
     pub fn build(grammar: Grammar, first_sets: FirstSets, follow_sets: FollowSets) -> Result<Self> {
-        let mut table: HashMap<(Symbol, Symbol), Production> = HashMap::new();
-
-        for production in grammar.all_productions() {
-            let lhs = production.lhs;
-            let rhs = &production.rhs;
-
-            // Compute FIRST(α)
-            let first_alpha = first_of_string(&first_sets, rhs);
-
-            // For each terminal in FIRST(α) - {ε}
-            for symbol in &first_alpha {
-                if !symbol.is_epsilon() {
-                    let key = (lhs, *symbol);
-
-                    // Check for conflicts
-                    if let Some(existing_prod) = table.get(&key) {
-                        return Err(GrammarError::LL1Conflict {
-                            nonterminal: lhs.to_string(),
-                            terminal: symbol.to_string(),
-                            prod1: existing_prod.to_string(),
-                            prod2: production.to_string(),
-                        });
-                    }
-
-                    table.insert(key, production.clone());
-                }
-            }
+        let table = build_table(&grammar, &first_sets, &follow_sets)?;
 
-            // If ε ∈ FIRST(α)
-            if first_alpha.contains(&Symbol::Epsilon) {
-                let follow_lhs = follow_sets.get(&lhs).cloned().unwrap_or_default();
+        Ok(Self {
+            grammar,
+            table,
+            first_sets,
+            follow_sets,
+        })
+    }
 
-                for symbol in &follow_lhs {
-                    let key = (lhs, *symbol);
+    /// Builds an LL(1) parser, eliminating left recursion and left-factoring
+    /// the grammar first if it is not LL(1) as given.
+    ///
+    /// Returns the parser together with the grammar it was built from, which
+    /// is the input grammar when no transformation was needed. Fails with
+    /// the conflict of the transformed grammar if it is still not LL(1).
+    pub fn build_auto(grammar: Grammar) -> Result<(Self, Grammar)> {
+        let first_sets = compute_first_sets(&grammar);
+        let follow_sets = compute_follow_sets(&grammar, &first_sets);
+        if let Ok(parser) = Self::build(grammar.clone(), first_sets, follow_sets) {
+            return Ok((parser, grammar));
+        }
 
-                    // Check for conflicts
-                    if let Some(existing_prod) = table.get(&key) {
-                        return Err(GrammarError::LL1Conflict {
-                            nonterminal: lhs.to_string(),
-                            terminal: symbol.to_string(),
-                            prod1: existing_prod.to_string(),
-                            prod2: production.to_string(),
-                        });
-                    }
+        let transformed = GrammarPipeline::new(grammar)
+            .eliminate_left_recursion()
+            .left_factor()
+            .build()?;
+        let first_sets = compute_first_sets(&transformed);
+        let follow_sets = compute_follow_sets(&transformed, &first_sets);
+        let parser = Self::build(transformed.clone(), first_sets, follow_sets)?;
+        Ok((parser, transformed))
+    }
 
-                    table.insert(key, production.clone());
-                }
-            }
+    /// Builds an LL(1) parser, reporting every conflict instead of the first.
+    ///
+    /// On failure the error is a [`GrammarError::ConflictReport`] listing each
+    /// conflicting cell of the parse table.
+    pub fn build_with_report(
+        grammar: Grammar,
+        first_sets: FirstSets,
+        follow_sets: FollowSets,
+    ) -> Result<Self> {
+        let (table, conflicts) = fill_table(&grammar, &first_sets, &follow_sets, false);
+        if !conflicts.is_empty() {
+            return Err(GrammarError::ConflictReport { conflicts });
         }
 
         Ok(Self {
@@ -86,6 +90,17 @@ impl LL1Parser {
         })
     }
 
+    /// Returns every LL(1) conflict of a grammar, in production order.
+    ///
+    /// An empty result means the grammar is LL(1).
+    pub fn conflicts(
+        grammar: &Grammar,
+        first_sets: &FirstSets,
+        follow_sets: &FollowSets,
+    ) -> Vec<GrammarError> {
+        fill_table(grammar, first_sets, follow_sets, false).1
+    }
+
     /// Parses an input string using the LL(1) parse table.
     ///
     /// # Algorithm
@@ -98,9 +113,33 @@ impl LL1Parser {
     /// - If top is terminal but ≠ input: reject
     /// - If table entry is empty: reject
     /// - Accept when stack is [$] and input is [$]
+    ///
+    /// The input is taken verbatim: every character, whitespace included, is
+    /// a symbol. Use [`parse_trimmed`](Self::parse_trimmed) for input typed
+    /// by a user, as the CLI does.
     pub fn parse(&self, input: &str) -> bool {
+        // With no limit the stack can never exceed it, so the only failure is
+        // a rejection
+        self.parse_with_limit(input, usize::MAX).unwrap_or(false)
+    }
+
+    /// Parses each input against the same table, in order.
+    ///
+    /// Equivalent to calling [`parse`](Self::parse) on every input; the table
+    /// is built once, so checking many strings costs only the parses.
+    pub fn parse_batch(&self, inputs: &[&str]) -> Vec<bool> {
+        inputs.iter().map(|input| self.parse(input)).collect()
+    }
+
+    /// Parses an input string like [`parse`](Self::parse), but fails with
+    /// [`GrammarError::StackLimitExceeded`] as soon as the stack holds more
+    /// than `max_stack` symbols.
+    ///
+    /// The stack of a predictive parser can grow with the input, so this
+    /// bounds the memory used on untrusted input. A rejection is `Ok(false)`.
+    pub fn parse_with_limit(&self, input: &str, max_stack: usize) -> Result<bool> {
         // Convert input to symbols and add $
-        let mut input_symbols = string_to_symbols(input);
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
         input_symbols.push(Symbol::EndMarker);
 
         // Initialize stack with [$, S]
@@ -129,27 +168,296 @@ impl LL1Parser {
                     stack.pop();
 
                     // Push RHS in reverse order (skip epsilon)
-                    if production.rhs != vec![Symbol::Epsilon] {
+                    if !production.is_epsilon() {
                         for symbol in production.rhs.iter().rev() {
                             stack.push(*symbol);
                         }
                     }
+                    if stack.len() > max_stack {
+                        return Err(GrammarError::StackLimitExceeded {
+                            limit: max_stack,
+                            position: input_index,
+                        });
+                    }
                 } else {
                     // No table entry - reject
-                    return false;
+                    return Ok(false);
                 }
             } else {
                 // Top is terminal but doesn't match input - reject
-                return false;
+                return Ok(false);
             }
         }
 
         // Accept if both stack and input are consumed
-        stack.is_empty() && input_index == input_symbols.len()
+        Ok(stack.is_empty() && input_index == input_symbols.len())
+    }
+
+    /// Parses an input string after removing leading and trailing
+    /// whitespace, matching how the CLI reads its input lines.
+    ///
+    /// Whitespace inside the input is kept, so `"i + i"` still fails for a
+    /// grammar without a space terminal.
+    pub fn parse_trimmed(&self, input: &str) -> bool {
+        self.parse(input.trim())
+    }
+
+    /// Parses an input string, explaining a rejection.
+    ///
+    /// Fails with [`GrammarError::NonterminalInInput`] if the input holds
+    /// anything but terminals. Otherwise a rejection is a
+    /// [`GrammarError::Rejected`] whose [`Rejection`] gives the position of
+    /// the first symbol the parser could not handle and the symbols it would
+    /// have accepted there: the terminal on top of the stack, or every
+    /// lookahead with a table entry for the nonterminal on top.
+    pub fn parse_detailed(&self, input: &str) -> Result<()> {
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
+        check_input(&input_symbols)?;
+        input_symbols.push(Symbol::EndMarker);
+
+        let start = self.grammar.start_symbol();
+        let mut stack = vec![Symbol::EndMarker, start];
+        let mut input_index = 0;
+
+        while let Some(&top) = stack.last() {
+            let current_input = input_symbols[input_index];
+
+            if top == current_input {
+                if top == Symbol::EndMarker {
+                    return Ok(());
+                }
+                stack.pop();
+                input_index += 1;
+                continue;
+            }
+
+            if top.is_nonterminal()
+                && let Some(production) = self.table.get(&(top, current_input))
+            {
+                stack.pop();
+                if !production.is_epsilon() {
+                    stack.extend(production.rhs.iter().rev());
+                }
+                continue;
+            }
+
+            let mut expected: Vec<Symbol> = if top.is_nonterminal() {
+                self.table
+                    .keys()
+                    .filter(|(nt, _)| *nt == top)
+                    .map(|(_, lookahead)| *lookahead)
+                    .collect()
+            } else {
+                vec![top]
+            };
+            expected.sort();
+            return Err(GrammarError::Rejected(Rejection {
+                position: input_index,
+                found: current_input,
+                expected,
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Parses an input string, recording every step of the parse.
+    ///
+    /// Each step shows the stack (bottom first), the remaining input and the
+    /// action taken: the production applied, `match a`, `accept` or `error`.
+    /// The verdict agrees with [`parse`](Self::parse).
+    pub fn parse_trace(&self, input: &str) -> ParseTrace {
+        let config = self.grammar.config();
+        let render = |symbols: &[Symbol]| -> String {
+            symbols
+                .iter()
+                .map(|s| config.symbol_to_string(*s))
+                .collect()
+        };
+
+        let mut input_symbols = config.string_to_symbols(input);
+        input_symbols.push(Symbol::EndMarker);
+
+        let start = self.grammar.start_symbol();
+        let mut stack = vec![Symbol::EndMarker, start];
+        let mut input_index = 0;
+        let mut trace = ParseTrace::default();
+
+        while let Some(&top) = stack.last() {
+            let current_input = input_symbols[input_index];
+            let (stack_text, input_text) = (render(&stack), render(&input_symbols[input_index..]));
+
+            if top == current_input {
+                if top == Symbol::EndMarker {
+                    trace.push(stack_text, input_text, "accept");
+                    trace.accepted = true;
+                    return trace;
+                }
+                trace.push(stack_text, input_text, format!("match {}", render(&[top])));
+                stack.pop();
+                input_index += 1;
+                continue;
+            }
+
+            match self.table.get(&(top, current_input)) {
+                Some(production) if top.is_nonterminal() => {
+                    trace.push(stack_text, input_text, production.to_string());
+                    stack.pop();
+                    if !production.is_epsilon() {
+                        stack.extend(production.rhs.iter().rev());
+                    }
+                }
+                _ => {
+                    trace.push(stack_text, input_text, "error");
+                    return trace;
+                }
+            }
+        }
+
+        trace
     }
 
     /// Returns a reference to the parse table.
     pub fn table(&self) -> &HashMap<(Symbol, Symbol), Production> {
         &self.table
     }
+
+    /// Returns the grammar this parser was built from.
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Returns the FIRST sets used to build the parse table.
+    pub fn first_sets(&self) -> &FirstSets {
+        &self.first_sets
+    }
+
+    /// Returns the FOLLOW sets used to build the parse table.
+    pub fn follow_sets(&self) -> &FollowSets {
+        &self.follow_sets
+    }
+
+    /// Renders the parse table as aligned text.
+    ///
+    /// Rows are the nonterminals and columns the terminals followed by `$`,
+    /// both in symbol order; empty cells are error entries.
+    pub fn table_string(&self) -> String {
+        self.table_string_with_order(ColumnOrder::Symbol)
+    }
+
+    /// Renders the parse table as aligned text with the terminal columns in
+    /// the given order; `$` is always the last column.
+    pub fn table_string_with_order(&self, order: ColumnOrder) -> String {
+        render_table(&self.table_rows(order))
+    }
+
+    /// Renders the parse table as a GitHub-flavored Markdown table.
+    ///
+    /// The layout is that of [`table_string`](Self::table_string): one row
+    /// per nonterminal and one column per terminal and `$`, in symbol order.
+    pub fn to_markdown_table(&self) -> String {
+        render_markdown(&self.table_rows(ColumnOrder::Symbol))
+    }
+
+    /// Returns the parse table as rows of cells, header first.
+    fn table_rows(&self, order: ColumnOrder) -> Vec<Vec<String>> {
+        let mut nonterminals: Vec<Symbol> = self.grammar.nonterminals().iter().copied().collect();
+        nonterminals.sort();
+        let mut columns = self.grammar.terminals_ordered(order);
+        columns.push(Symbol::EndMarker);
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut header = vec![String::new()];
+        let config = self.grammar.config();
+        header.extend(columns.iter().map(|c| config.symbol_to_string(*c)));
+        rows.push(header);
+        for nt in &nonterminals {
+            let mut row = vec![nt.to_string()];
+            row.extend(columns.iter().map(|c| {
+                self.table
+                    .get(&(*nt, *c))
+                    .map(|p| p.to_string())
+                    .unwrap_or_default()
+            }));
+            rows.push(row);
+        }
+        rows
+    }
+}
+
+/// Builds the LL(1) parse table, stopping at the first conflicting cell.
+pub(crate) fn build_table(
+    grammar: &Grammar,
+    first_sets: &FirstSets,
+    follow_sets: &FollowSets,
+) -> Result<ParseTable> {
+    let (table, conflicts) = fill_table(grammar, first_sets, follow_sets, true);
+    match conflicts.into_iter().next() {
+        Some(conflict) => Err(conflict),
+        None => Ok(table),
+    }
+}
+
+/// Fills the LL(1) parse table, collecting the conflicting cells.
+///
+/// # Algorithm
+/// For each production A → α:
+/// 1. For each terminal a in FIRST(α), add A → α to M[A, a]
+/// 2. If ε ∈ FIRST(α), for each b in FOLLOW(A), add A → α to M[A, b]
+///
+/// A cell that is already filled keeps its first production. With
+/// `stop_at_first`, filling ends at the first conflict, leaving the table
+/// incomplete; otherwise every conflict is collected.
+fn fill_table(
+    grammar: &Grammar,
+    first_sets: &FirstSets,
+    follow_sets: &FollowSets,
+    stop_at_first: bool,
+) -> (ParseTable, Vec<GrammarError>) {
+    let mut table: ParseTable = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for production in grammar.all_productions() {
+        let lhs = production.lhs;
+        let rhs = &production.rhs;
+
+        // Compute FIRST(α)
+        let first_alpha = first_of_string(first_sets, rhs);
+
+        // Lookaheads: FIRST(α) - {ε}, plus FOLLOW(A) if ε ∈ FIRST(α)
+        let mut lookaheads: Vec<Symbol> = first_alpha
+            .iter()
+            .filter(|s| !s.is_epsilon())
+            .copied()
+            .collect();
+        if first_alpha.contains(&Symbol::Epsilon) {
+            lookaheads.extend(follow_sets.follow_of(lhs));
+        }
+        // Sorted so the reported conflicts are deterministic; a symbol in both
+        // FIRST(α) and FOLLOW(A) selects the same production, not a conflict
+        lookaheads.sort();
+        lookaheads.dedup();
+
+        for symbol in lookaheads {
+            let key = (lhs, symbol);
+
+            // Check for conflicts
+            if let Some(existing_prod) = table.get(&key) {
+                conflicts.push(GrammarError::LL1Conflict {
+                    nonterminal: lhs.to_string(),
+                    terminal: symbol.to_string(),
+                    prod1: existing_prod.to_string(),
+                    prod2: production.to_string(),
+                });
+                if stop_at_first {
+                    return (table, conflicts);
+                }
+                continue;
+            }
+
+            table.insert(key, production.clone());
+        }
+    }
+
+    (table, conflicts)
 }
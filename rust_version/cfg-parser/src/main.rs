@@ -19,7 +19,9 @@ use cfg_parser::cli;
 use std::process;
 
 fn main() {
-    if let Err(e) = cli::run() {
+    let result = cli::Options::from_args(std::env::args().skip(1))
+        .and_then(|options| cli::run_with_options(&options));
+    if let Err(e) = result {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
@@ -4,10 +4,12 @@
 //! with lookahead from FOLLOW sets.
 
 use crate::error::{GrammarError, Result};
-use crate::first_follow::FollowSets;
-use crate::grammar::{Grammar, Production};
-use crate::symbol::{string_to_symbols, Symbol};
+use crate::first_follow::{compute_first_sets, compute_follow_sets, format_set, FollowSets};
+use crate::grammar::{ColumnOrder, Grammar, GrammarChange, Production};
+use crate::symbol::{symbols_to_string, Symbol};
+use crate::trace::{check_input, ParseTrace, Rejection};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 /// An LR(0) item: a production with a dot position.
 ///
@@ -28,37 +30,105 @@ impl Item {
     }
 
     /// Returns the symbol after the dot, if any.
+    ///
+    /// An ε-production has an empty body, so nothing follows its dot.
     fn symbol_after_dot(&self) -> Option<Symbol> {
+        if self.production.is_epsilon() {
+            return None;
+        }
         self.production.rhs.get(self.dot_position).copied()
     }
 
     /// Checks if the dot is at the end (reduce item).
     fn is_reduce_item(&self) -> bool {
-        self.dot_position >= self.production.rhs.len()
+        self.production.is_epsilon() || self.dot_position >= self.production.rhs.len()
     }
 }
 
 /// A state in the LR(0) automaton (set of items).
 type ItemSet = HashSet<Item>;
 
-/// SLR(1) action.
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Action {
+/// An entry of the SLR(1) ACTION table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Shift the lookahead and go to the given state
     Shift(usize),
-    Reduce(Production),
+    /// Reduce by the production with the given number, its 1-based position
+    /// in the grammar (see [`Grammar::productions_indexed`])
+    Reduce(usize),
+    /// Accept the input
     Accept,
 }
 
+impl Action {
+    /// Checks if this is a shift action.
+    pub fn is_shift(&self) -> bool {
+        matches!(self, Action::Shift(_))
+    }
+
+    /// Checks if this is a reduce action.
+    pub fn is_reduce(&self) -> bool {
+        matches!(self, Action::Reduce(_))
+    }
+
+    /// Checks if this is the accept action.
+    pub fn is_accept(&self) -> bool {
+        matches!(self, Action::Accept)
+    }
+
+    /// Returns the production a reduce action reduces by, resolved in the
+    /// grammar the table was built from.
+    pub fn production<'a>(&self, grammar: &'a Grammar) -> Option<&'a Production> {
+        match self {
+            Action::Reduce(number) => grammar.production(*number),
+            _ => None,
+        }
+    }
+}
+
+/// Displays the action as `s5`, `r3` or `acc`.
+///
+/// Reductions show the production number, its 1-based position in the
+/// grammar; 0 is reserved for the augmented production S' → S.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Shift(state) => write!(f, "s{}", state),
+            Action::Reduce(number) => write!(f, "r{}", number),
+            Action::Accept => write!(f, "acc"),
+        }
+    }
+}
+
+/// ACTION table: (state, terminal/end_marker) → Action
+pub type ActionTable = HashMap<(usize, Symbol), Action>;
+
+/// GOTO table: (state, nonterminal) → state
+pub type GotoTable = HashMap<(usize, Symbol), usize>;
+
+/// Counts of the work done by one SLR(1) parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseRunStats {
+    /// Number of shift actions taken
+    pub shifts: usize,
+    /// Number of reductions performed
+    pub reduces: usize,
+    /// Largest number of states on the stack at any point
+    pub max_stack_depth: usize,
+}
+
+/// The start symbol of the augmented grammar (S' → S).
+const AUGMENTED_START: Symbol = Symbol::Special("S'");
+
 /// SLR(1) parser.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SLR1Parser {
     grammar: Grammar,
-    augmented_start: Symbol,
     states: Vec<ItemSet>,
     /// ACTION table: (state, terminal/end_marker) → Action
-    action_table: HashMap<(usize, Symbol), Action>,
+    action_table: ActionTable,
     /// GOTO table: (state, nonterminal) → state
-    goto_table: HashMap<(usize, Symbol), usize>,
+    goto_table: GotoTable,
 }
 
 impl SLR1Parser {
@@ -66,31 +136,246 @@ impl SLR1Parser {
     pub fn build(grammar: Grammar, follow_sets: FollowSets) -> Result<Self> {
         // Create augmented grammar with S' → S
         let start = grammar.start_symbol();
-        let augmented_start = Symbol::Nonterminal('\'');
+        let augmented_start = AUGMENTED_START;
         let start_production = Production::new(augmented_start, vec![start]);
 
         // Build LR(0) automaton
         let (states, transitions) = Self::build_lr0_automaton(&grammar, &start_production);
 
         // Build ACTION and GOTO tables
-        let (action_table, goto_table) = Self::build_tables(
+        let (action_table, goto_table, conflicts) = Self::build_tables(
             &grammar,
             &states,
             &transitions,
             &follow_sets,
             augmented_start,
-            &start_production,
-        )?;
+        );
+        if let Some((_, conflict)) = conflicts.into_iter().next() {
+            return Err(conflict);
+        }
 
         Ok(Self {
             grammar,
-            augmented_start,
             states,
             action_table,
             goto_table,
         })
     }
 
+    /// Builds an SLR(1) parser, reporting every conflict instead of the first.
+    ///
+    /// On failure the error is a [`GrammarError::ConflictReport`] listing each
+    /// conflicting ACTION cell.
+    pub fn build_with_report(grammar: Grammar, follow_sets: FollowSets) -> Result<Self> {
+        let conflicts = Self::conflicts(&grammar, &follow_sets);
+        if !conflicts.is_empty() {
+            return Err(GrammarError::ConflictReport { conflicts });
+        }
+        Self::build(grammar, follow_sets)
+    }
+
+    /// Builds an SLR(1) parser like [`build`](Self::build), but on a conflict
+    /// returns a [`GrammarError::ConflictInState`] that also lists the items
+    /// of the conflicting state, kernel items first.
+    pub fn build_verbose(grammar: Grammar, follow_sets: FollowSets) -> Result<Self> {
+        let conflict = match Self::build(grammar.clone(), follow_sets) {
+            Ok(parser) => return Ok(parser),
+            Err(conflict) => conflict,
+        };
+        let state = match &conflict {
+            GrammarError::SLR1ShiftReduceConflict { state, .. }
+            | GrammarError::SLR1ReduceReduceConflict { state, .. } => *state,
+            _ => return Err(conflict),
+        };
+
+        let start_production = Production::new(AUGMENTED_START, vec![grammar.start_symbol()]);
+        let (states, _) = Self::build_lr0_automaton(&grammar, &start_production);
+        let items = sorted_items(&grammar, &states[state])
+            .into_iter()
+            .map(|item| item_label(&grammar, item))
+            .collect();
+        Err(GrammarError::ConflictInState {
+            conflict: Box::new(conflict),
+            state,
+            items,
+        })
+    }
+
+    /// Builds the SLR(1) tables and returns every conflict found, in state order.
+    ///
+    /// Unlike [`build`](Self::build), which stops at the first conflict, this
+    /// reports all of them; an empty result means the grammar is SLR(1).
+    pub fn conflicts(grammar: &Grammar, follow_sets: &FollowSets) -> Vec<GrammarError> {
+        let start_production = Production::new(AUGMENTED_START, vec![grammar.start_symbol()]);
+        let (states, transitions) = Self::build_lr0_automaton(grammar, &start_production);
+        let (_, _, conflicts) =
+            Self::build_tables(grammar, &states, &transitions, follow_sets, AUGMENTED_START);
+        conflicts
+            .into_iter()
+            .map(|(_, conflict)| conflict)
+            .collect()
+    }
+
+    /// Explains the first SLR(1) conflict of a grammar in prose, or returns
+    /// `None` if the grammar is SLR(1).
+    ///
+    /// The explanation names the state and lookahead, then lists each item
+    /// behind the conflict: shift items because the lookahead follows their
+    /// dot, reduce items because the lookahead is in FOLLOW of their
+    /// left-hand side. The first conflict is the one with the lowest state
+    /// number, then lookahead.
+    pub fn explain_conflict(grammar: &Grammar, follow_sets: &FollowSets) -> Option<String> {
+        let start_production = Production::new(AUGMENTED_START, vec![grammar.start_symbol()]);
+        let (states, transitions) = Self::build_lr0_automaton(grammar, &start_production);
+        let (_, _, conflicts) =
+            Self::build_tables(grammar, &states, &transitions, follow_sets, AUGMENTED_START);
+
+        let (state, symbol, kind) = conflicts
+            .iter()
+            .filter_map(|(symbol, conflict)| match conflict {
+                GrammarError::SLR1ShiftReduceConflict { state, .. } => {
+                    Some((*state, *symbol, "shift/reduce"))
+                }
+                GrammarError::SLR1ReduceReduceConflict { state, .. } => {
+                    Some((*state, *symbol, "reduce/reduce"))
+                }
+                _ => None,
+            })
+            .min()?;
+
+        // Shift items before reduce items, each group in item order
+        let mut items: Vec<&Item> = states[state].iter().collect();
+        items.sort_by_key(|item| {
+            (
+                item.is_reduce_item(),
+                production_number(grammar, &item.production),
+                item.dot_position,
+            )
+        });
+
+        let symbol_name = grammar.config().symbol_to_string(symbol);
+        let mut lines = Vec::new();
+        for item in items {
+            let label = item_label(grammar, item);
+            if item.is_reduce_item() {
+                let follow = follow_sets.follow_of(item.production.lhs);
+                if item.production.lhs != AUGMENTED_START && follow.contains(&symbol) {
+                    lines.push(format!(
+                        "  reduce by {} because {} is in FOLLOW({}) = {}",
+                        label,
+                        symbol_name,
+                        item.production.lhs,
+                        format_set(follow, grammar.config())
+                    ));
+                }
+            } else if item.symbol_after_dot() == Some(symbol) {
+                lines.push(format!(
+                    "  shift in {} because {} follows the dot",
+                    label, symbol_name
+                ));
+            }
+        }
+
+        Some(format!(
+            "SLR(1) {} conflict in state {} on lookahead {}:\n{}\n",
+            kind,
+            state,
+            symbol_name,
+            lines.join("\n")
+        ))
+    }
+
+    /// Rebuilds the parser after a single-production edit, reusing the
+    /// existing tables when the edit cannot affect them.
+    ///
+    /// The check is conservative, see [`is_affected_by`](Self::is_affected_by):
+    /// when the tables are reused the grammar is swapped and the reduce
+    /// actions are renumbered, since removing a production shifts the
+    /// numbers of those after it. Otherwise the parser is rebuilt from
+    /// scratch.
+    pub fn rebuild_incremental(&self, change: &GrammarChange) -> Result<SLR1Parser> {
+        let grammar = self.grammar.apply_change(change)?;
+        if !self.is_affected_with(&grammar, change)
+            && let Some(action_table) = self.renumber_reductions(&grammar)
+        {
+            return Ok(Self {
+                grammar,
+                action_table,
+                ..self.clone()
+            });
+        }
+
+        let first_sets = compute_first_sets(&grammar);
+        let follow_sets = compute_follow_sets(&grammar, &first_sets);
+        Self::build(grammar, follow_sets)
+    }
+
+    /// Maps the reduce actions to the numbers of their productions in
+    /// `grammar`, or returns `None` if one of them is not in it.
+    fn renumber_reductions(&self, grammar: &Grammar) -> Option<ActionTable> {
+        self.action_table
+            .iter()
+            .map(|(&key, action)| match action {
+                Action::Reduce(number) => {
+                    let production = self.grammar.production(*number)?;
+                    Some((key, Action::Reduce(grammar.production_index(production)?)))
+                }
+                other => Some((key, other.clone())),
+            })
+            .collect()
+    }
+
+    /// Checks whether a single-production edit can change the SLR(1) tables.
+    ///
+    /// An edit is known not to affect them when no touched production's
+    /// left-hand side occurs in any LR(0) item, on either side of the arrow,
+    /// and the FOLLOW sets of every nonterminal that does occur stay the
+    /// same. Anything else counts as affected; in particular, giving a
+    /// referenced but undefined nonterminal its first production does.
+    pub fn is_affected_by(&self, change: &GrammarChange) -> Result<bool> {
+        let grammar = self.grammar.apply_change(change)?;
+        Ok(self.is_affected_with(&grammar, change))
+    }
+
+    fn is_affected_with(&self, grammar: &Grammar, change: &GrammarChange) -> bool {
+        let used: HashSet<Symbol> = self
+            .states
+            .iter()
+            .flatten()
+            .flat_map(|item| std::iter::once(&item.production.lhs).chain(&item.production.rhs))
+            .filter(|symbol| symbol.is_nonterminal())
+            .copied()
+            .collect();
+        if change.productions().iter().any(|p| used.contains(&p.lhs)) {
+            return true;
+        }
+
+        let old_first = compute_first_sets(&self.grammar);
+        let old_follow = compute_follow_sets(&self.grammar, &old_first);
+        let new_first = compute_first_sets(grammar);
+        let new_follow = compute_follow_sets(grammar, &new_first);
+        used.iter()
+            .any(|nt| old_follow.follow_of(*nt) != new_follow.follow_of(*nt))
+    }
+
+    /// Exports the LR(0) automaton of any grammar in Graphviz DOT format,
+    /// highlighting the states with SLR(1) conflicts.
+    ///
+    /// Conflicting states are drawn in red and list each symbol they conflict
+    /// on, which makes the diagram a debugging aid for grammars that
+    /// [`build`](Self::build) rejects.
+    pub fn to_dot_with_conflicts(grammar: &Grammar, follow_sets: &FollowSets) -> String {
+        let start_production = Production::new(AUGMENTED_START, vec![grammar.start_symbol()]);
+        let (states, transitions) = Self::build_lr0_automaton(grammar, &start_production);
+        let (_, _, conflicts) =
+            Self::build_tables(grammar, &states, &transitions, follow_sets, AUGMENTED_START);
+        let conflicts: Vec<GrammarError> = conflicts
+            .into_iter()
+            .map(|(_, conflict)| conflict)
+            .collect();
+        render_dot(grammar, &states, &transitions, &conflicts)
+    }
+
     /// Computes the closure of a set of items.
     ///
     /// For each item [A → α•Bβ] where B is nonterminal,
@@ -104,14 +389,14 @@ impl SLR1Parser {
             let current = result.clone();
 
             for item in &current {
-                if let Some(symbol) = item.symbol_after_dot() {
-                    if symbol.is_nonterminal() {
-                        for production in grammar.get_productions(symbol) {
-                            let new_item = Item::new(production.clone(), 0);
-                            if !result.contains(&new_item) {
-                                result.insert(new_item);
-                                changed = true;
-                            }
+                if let Some(symbol) = item.symbol_after_dot()
+                    && symbol.is_nonterminal()
+                {
+                    for production in grammar.get_productions(symbol) {
+                        let new_item = Item::new(production.clone(), 0);
+                        if !result.contains(&new_item) {
+                            result.insert(new_item);
+                            changed = true;
                         }
                     }
                 }
@@ -126,11 +411,9 @@ impl SLR1Parser {
         let mut moved = ItemSet::new();
 
         for item in items {
-            if let Some(sym) = item.symbol_after_dot() {
-                if sym == symbol {
-                    let new_item = Item::new(item.production.clone(), item.dot_position + 1);
-                    moved.insert(new_item);
-                }
+            if item.symbol_after_dot() == Some(symbol) {
+                let new_item = Item::new(item.production.clone(), item.dot_position + 1);
+                moved.insert(new_item);
             }
         }
 
@@ -153,13 +436,14 @@ impl SLR1Parser {
         while let Some(state_id) = worklist.pop_front() {
             let state = states[state_id].clone();
 
-            // Get all symbols that can be shifted
-            let mut symbols = HashSet::new();
-            for item in &state {
-                if let Some(symbol) = item.symbol_after_dot() {
-                    symbols.insert(symbol);
-                }
-            }
+            // Get all symbols that can be shifted, in symbol order so that
+            // state numbering is deterministic
+            let mut symbols: Vec<Symbol> = state
+                .iter()
+                .filter_map(|item| item.symbol_after_dot())
+                .collect();
+            symbols.sort();
+            symbols.dedup();
 
             // For each symbol, compute goto and add new states
             for symbol in symbols {
@@ -183,35 +467,43 @@ impl SLR1Parser {
     }
 
     /// Builds ACTION and GOTO tables for SLR(1).
-    #[allow(clippy::too_many_arguments)]
+    ///
+    /// Conflicting cells keep their first action; every conflict is collected,
+    /// with the lookahead it is on, and returned alongside the tables.
     fn build_tables(
-        _grammar: &Grammar,
+        grammar: &Grammar,
         states: &[ItemSet],
         transitions: &HashMap<(usize, Symbol), usize>,
         follow_sets: &FollowSets,
         augmented_start: Symbol,
-        _start_production: &Production,
-    ) -> Result<(
-        HashMap<(usize, Symbol), Action>,
-        HashMap<(usize, Symbol), usize>,
-    )> {
+    ) -> (ActionTable, GotoTable, Vec<(Symbol, GrammarError)>) {
         let mut action_table = HashMap::new();
         let mut goto_table = HashMap::new();
+        let mut conflicts = Vec::new();
 
         for (state_id, state) in states.iter().enumerate() {
             for item in state {
                 if !item.is_reduce_item() {
                     // Shift items: [A → α•aβ] where a is terminal
-                    if let Some(symbol) = item.symbol_after_dot() {
-                        if symbol.is_terminal() || symbol.is_end_marker() {
-                            if let Some(&next_state) = transitions.get(&(state_id, symbol)) {
-                                let key = (state_id, symbol);
-                                if action_table.contains_key(&key) {
-                                    return Err(GrammarError::SLR1ShiftReduceConflict {
+                    if let Some(symbol) = item.symbol_after_dot()
+                        && symbol.is_terminal()
+                        && let Some(&next_state) = transitions.get(&(state_id, symbol))
+                    {
+                        let key = (state_id, symbol);
+                        match action_table.get(&key) {
+                            Some(Action::Reduce(number)) => {
+                                let production = &grammar.all_productions()[number - 1];
+                                conflicts.push((
+                                    symbol,
+                                    GrammarError::SLR1ShiftReduceConflict {
                                         state: state_id,
                                         symbol: symbol.to_string(),
-                                    });
-                                }
+                                        epsilon_reduce: production.is_epsilon(),
+                                    },
+                                ));
+                            }
+                            Some(_) => {}
+                            None => {
                                 action_table.insert(key, Action::Shift(next_state));
                             }
                         }
@@ -223,11 +515,15 @@ impl SLR1Parser {
                         let key = (state_id, Symbol::EndMarker);
                         action_table.insert(key, Action::Accept);
                     } else {
-                        // Reduce on FOLLOW(A)
-                        let follow_a = follow_sets
-                            .get(&item.production.lhs)
-                            .cloned()
-                            .unwrap_or_default();
+                        // Reduce on FOLLOW(A), in symbol order so conflicts are
+                        // reported deterministically
+                        let mut follow_a: Vec<Symbol> = follow_sets
+                            .follow_of(item.production.lhs)
+                            .iter()
+                            .copied()
+                            .collect();
+                        follow_a.sort();
+                        let number = production_number(grammar, &item.production);
 
                         for symbol in follow_a {
                             let key = (state_id, symbol);
@@ -235,23 +531,31 @@ impl SLR1Parser {
                             if let Some(existing) = action_table.get(&key) {
                                 match existing {
                                     Action::Shift(_) => {
-                                        return Err(GrammarError::SLR1ShiftReduceConflict {
-                                            state: state_id,
-                                            symbol: symbol.to_string(),
-                                        });
+                                        conflicts.push((
+                                            symbol,
+                                            GrammarError::SLR1ShiftReduceConflict {
+                                                state: state_id,
+                                                symbol: symbol.to_string(),
+                                                epsilon_reduce: item.production.is_epsilon(),
+                                            },
+                                        ));
                                     }
-                                    Action::Reduce(other_prod) => {
-                                        return Err(GrammarError::SLR1ReduceReduceConflict {
-                                            state: state_id,
-                                            symbol: symbol.to_string(),
-                                            prod1: other_prod.to_string(),
-                                            prod2: item.production.to_string(),
-                                        });
+                                    Action::Reduce(other) => {
+                                        conflicts.push((
+                                            symbol,
+                                            GrammarError::SLR1ReduceReduceConflict {
+                                                state: state_id,
+                                                symbol: symbol.to_string(),
+                                                prod1: grammar.all_productions()[other - 1]
+                                                    .to_string(),
+                                                prod2: item.production.to_string(),
+                                            },
+                                        ));
                                     }
                                     Action::Accept => {}
                                 }
                             } else {
-                                action_table.insert(key, Action::Reduce(item.production.clone()));
+                                action_table.insert(key, Action::Reduce(number));
                             }
                         }
                     }
@@ -267,66 +571,784 @@ impl SLR1Parser {
             }
         }
 
-        Ok((action_table, goto_table))
+        (action_table, goto_table, conflicts)
+    }
+
+    /// Returns the grammar this parser was built from.
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// Returns the ACTION table.
+    pub fn action_table(&self) -> &ActionTable {
+        &self.action_table
+    }
+
+    /// Returns the GOTO table.
+    pub fn goto_table(&self) -> &GotoTable {
+        &self.goto_table
+    }
+
+    /// Returns the productions that no state of the ACTION table reduces by,
+    /// in production order.
+    ///
+    /// Such productions can never take part in a parse: they usually belong to
+    /// a nonterminal unreachable from the start symbol, or are redundant.
+    pub fn unused_reductions(&self) -> Vec<Production> {
+        let used: HashSet<usize> = self
+            .action_table
+            .values()
+            .filter_map(|action| match action {
+                Action::Reduce(number) => Some(*number),
+                _ => None,
+            })
+            .collect();
+        self.grammar
+            .all_productions()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !used.contains(&(i + 1)))
+            .map(|(_, production)| production.clone())
+            .collect()
+    }
+
+    /// Returns the productions reduced by while parsing the accepted inputs.
+    ///
+    /// This is test coverage for a grammar: a production missing from the
+    /// result is never exercised by the inputs. Rejected inputs contribute
+    /// nothing, since their reductions are not part of any derivation.
+    pub fn coverage(&self, inputs: &[&str]) -> HashSet<Production> {
+        inputs
+            .iter()
+            .filter_map(|input| self.action_sequence(input).ok())
+            .flatten()
+            .filter_map(|action| match action {
+                Action::Reduce(number) => Some(self.grammar.all_productions()[number - 1].clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Renders the canonical collection of LR(0) items, one line per state
+    /// such as `I0: { S' → •S, S → •aS }`.
+    ///
+    /// Within a state the kernel items come first, then the items added by
+    /// closure, as in [`to_dot`](Self::to_dot).
+    pub fn collection_string(&self) -> String {
+        self.states
+            .iter()
+            .enumerate()
+            .map(|(id, state)| {
+                let items: Vec<String> = sorted_items(&self.grammar, state)
+                    .into_iter()
+                    .map(|item| item_label(&self.grammar, item))
+                    .collect();
+                format!("I{}: {{ {} }}\n", id, items.join(", "))
+            })
+            .collect()
+    }
+
+    /// Returns the number of states in the LR(0) automaton.
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the number of states left after [`compact`](Self::compact).
+    pub fn minimal_state_count(&self) -> usize {
+        self.clone().compact().num_states()
+    }
+
+    /// Merges states whose ACTION and GOTO rows are identical.
+    ///
+    /// Rows are compared with their target states mapped through the merges
+    /// found so far, repeating until nothing more merges, so states that only
+    /// differ by pointing at equivalent states collapse as well. This is
+    /// table-row equivalence rather than LALR merging: parsing gives the same
+    /// verdicts, with state 0 still the initial state.
+    pub fn compact(self) -> SLR1Parser {
+        type Row = (Vec<(Symbol, Action)>, Vec<(Symbol, usize)>);
+
+        let count = self.states.len();
+        let mut representative: Vec<usize> = (0..count).collect();
+
+        loop {
+            let remap = |action: &Action| match action {
+                Action::Shift(to) => Action::Shift(representative[*to]),
+                other => other.clone(),
+            };
+
+            let mut owners: HashMap<Row, usize> = HashMap::new();
+            let mut merged = representative.clone();
+            for (state, owner) in merged.iter_mut().enumerate() {
+                let mut actions: Vec<(Symbol, Action)> = self
+                    .action_table
+                    .iter()
+                    .filter(|((from, _), _)| *from == state)
+                    .map(|(&(_, symbol), action)| (symbol, remap(action)))
+                    .collect();
+                actions.sort_by_key(|(symbol, _)| *symbol);
+                let mut gotos: Vec<(Symbol, usize)> = self
+                    .goto_table
+                    .iter()
+                    .filter(|((from, _), _)| *from == state)
+                    .map(|(&(_, symbol), &to)| (symbol, representative[to]))
+                    .collect();
+                gotos.sort();
+
+                *owner = *owners.entry((actions, gotos)).or_insert(state);
+            }
+
+            if merged == representative {
+                break;
+            }
+            representative = merged;
+        }
+
+        // Renumber the surviving states densely, keeping their relative order
+        let mut new_id = vec![0; count];
+        let mut states: Vec<ItemSet> = Vec::new();
+        for state in 0..count {
+            if representative[state] == state {
+                new_id[state] = states.len();
+                states.push(ItemSet::new());
+            }
+        }
+        for (state, items) in self.states.into_iter().enumerate() {
+            states[new_id[representative[state]]].extend(items);
+        }
+
+        let action_table = self
+            .action_table
+            .into_iter()
+            .filter(|((from, _), _)| representative[*from] == *from)
+            .map(|((from, symbol), action)| {
+                let action = match action {
+                    Action::Shift(to) => Action::Shift(new_id[representative[to]]),
+                    other => other,
+                };
+                ((new_id[from], symbol), action)
+            })
+            .collect();
+        let goto_table = self
+            .goto_table
+            .into_iter()
+            .filter(|((from, _), _)| representative[*from] == *from)
+            .map(|((from, symbol), to)| ((new_id[from], symbol), new_id[representative[to]]))
+            .collect();
+
+        SLR1Parser {
+            grammar: self.grammar,
+            states,
+            action_table,
+            goto_table,
+        }
+    }
+
+    /// Exports the LR(0) automaton in Graphviz DOT format.
+    ///
+    /// Each state is a box listing its items; edges are the shift and GOTO
+    /// transitions labelled with their symbol.
+    pub fn to_dot(&self) -> String {
+        let transitions: HashMap<(usize, Symbol), usize> = self
+            .action_table
+            .iter()
+            .filter_map(|(&key, action)| match action {
+                Action::Shift(to) => Some((key, *to)),
+                _ => None,
+            })
+            .chain(self.goto_table.iter().map(|(&key, &to)| (key, to)))
+            .collect();
+
+        render_dot(&self.grammar, &self.states, &transitions, &[])
+    }
+
+    /// Exports the ACTION and GOTO tables as CSV.
+    ///
+    /// There is one row per state; the columns are the terminals and `$`
+    /// (ACTION) followed by the nonterminals (GOTO), each in symbol order.
+    /// Reductions refer to productions by number, see the grammar order.
+    pub fn to_csv(&self) -> String {
+        self.to_csv_with_order(ColumnOrder::Symbol)
+    }
+
+    /// Exports the ACTION and GOTO tables as CSV with the terminal columns in
+    /// the given order; `$` always follows the terminals.
+    pub fn to_csv_with_order(&self, order: ColumnOrder) -> String {
+        let mut terminals = self.grammar.terminals_ordered(order);
+        terminals.push(Symbol::EndMarker);
+        let mut nonterminals: Vec<Symbol> = self.grammar.nonterminals().iter().copied().collect();
+        nonterminals.sort();
+
+        let mut header = vec!["state".to_string()];
+        let config = self.grammar.config();
+        header.extend(
+            terminals
+                .iter()
+                .chain(&nonterminals)
+                .map(|s| config.symbol_to_string(*s)),
+        );
+        let mut output = csv_row(&header);
+
+        for state in 0..self.states.len() {
+            let mut row = vec![state.to_string()];
+            row.extend(terminals.iter().map(|t| {
+                self.action_table
+                    .get(&(state, *t))
+                    .map(|action| action.to_string())
+                    .unwrap_or_default()
+            }));
+            row.extend(nonterminals.iter().map(|nt| {
+                self.goto_table
+                    .get(&(state, *nt))
+                    .map(|next| next.to_string())
+                    .unwrap_or_default()
+            }));
+            output.push_str(&csv_row(&row));
+        }
+
+        output
     }
 
     /// Parses an input string using SLR(1) shift-reduce algorithm.
+    ///
+    /// The input is taken verbatim: every character, whitespace included, is
+    /// a symbol. Use [`parse_trimmed`](Self::parse_trimmed) for input typed
+    /// by a user, as the CLI does.
     pub fn parse(&self, input: &str) -> bool {
+        self.parse_with_stats(input).0
+    }
+
+    /// Parses each input against the same ACTION and GOTO tables, in order.
+    ///
+    /// Equivalent to calling [`parse`](Self::parse) on every input.
+    pub fn parse_batch(&self, inputs: &[&str]) -> Vec<bool> {
+        inputs.iter().map(|input| self.parse(input)).collect()
+    }
+
+    /// Parses an input string, also counting the work done.
+    ///
+    /// The statistics cover the run up to acceptance or the parse error, so
+    /// rejected inputs report how far the parser got.
+    pub fn parse_with_stats(&self, input: &str) -> (bool, ParseRunStats) {
         // Convert input to symbols and add $
-        let mut input_symbols = string_to_symbols(input);
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
         input_symbols.push(Symbol::EndMarker);
 
         // Initialize stack with state 0
         let mut stack: Vec<usize> = vec![0];
-        let mut symbol_stack: Vec<Symbol> = Vec::new();
+        let mut stats = ParseRunStats {
+            max_stack_depth: 1,
+            ..ParseRunStats::default()
+        };
+
+        for symbol in input_symbols {
+            match self.consume(&mut stack, symbol, &mut stats) {
+                Some(Action::Accept) => return (true, stats),
+                Some(_) => {}
+                None => return (false, stats),
+            }
+        }
+
+        (false, stats)
+    }
+
+    /// Parses an input string and returns the ACTION-table actions taken, in
+    /// order: every shift and reduction, ending with [`Action::Accept`].
+    ///
+    /// This is the action column of [`parse_trace`](Self::parse_trace)
+    /// without the stacks. A rejected input fails as in
+    /// [`parse_detailed`](Self::parse_detailed).
+    pub fn action_sequence(&self, input: &str) -> Result<Vec<Action>> {
+        self.parse_detailed(input)?;
+
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
+        input_symbols.push(Symbol::EndMarker);
+        let mut stack: Vec<usize> = vec![0];
+        let mut actions = Vec::new();
+        for symbol in input_symbols {
+            let action = self.consume_logged(
+                &mut stack,
+                symbol,
+                &mut ParseRunStats::default(),
+                Some(&mut actions),
+            );
+            if !matches!(action, Some(Action::Shift(_))) {
+                break;
+            }
+        }
+
+        Ok(actions)
+    }
+
+    /// Parses an input string after removing leading and trailing
+    /// whitespace, matching how the CLI reads its input lines.
+    ///
+    /// Whitespace inside the input is kept, so `"i + i"` still fails for a
+    /// grammar without a space terminal.
+    pub fn parse_trimmed(&self, input: &str) -> bool {
+        self.parse(input.trim())
+    }
+
+    /// Parses an input string, explaining a rejection.
+    ///
+    /// Fails with [`GrammarError::NonterminalInInput`] if the input holds
+    /// anything but terminals. Otherwise a rejection is a
+    /// [`GrammarError::Rejected`] whose [`Rejection`] gives the position of
+    /// the first symbol that could not be shifted and the symbols that could
+    /// have been shifted (or accepted) in its place. Unlike [`valid_next`](Self::valid_next),
+    /// lookaheads that only lead to a reduction and then an error are left
+    /// out.
+    pub fn parse_detailed(&self, input: &str) -> Result<()> {
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
+        check_input(&input_symbols)?;
+        input_symbols.push(Symbol::EndMarker);
+
+        let mut stack: Vec<usize> = vec![0];
+        let mut stats = ParseRunStats::default();
+
+        for (position, symbol) in input_symbols.into_iter().enumerate() {
+            let before = stack.clone();
+            match self.consume(&mut stack, symbol, &mut stats) {
+                Some(Action::Accept) => return Ok(()),
+                Some(_) => {}
+                None => {
+                    return Err(GrammarError::Rejected(
+                        self.rejection(&before, position, symbol),
+                    ));
+                }
+            }
+        }
+
+        // Only reachable if $ were shifted, which the tables never do
+        Err(GrammarError::Rejected(Rejection {
+            position: input.chars().count(),
+            found: Symbol::EndMarker,
+            expected: Vec::new(),
+        }))
+    }
+
+    /// Builds the rejection for `symbol` at `position`, given the stack
+    /// before the symbol was read.
+    ///
+    /// A lookahead in the state's row may still fail after its reductions, so
+    /// only those that get shifted or accepted are expected. The `error`
+    /// token never is, as it cannot be typed.
+    fn rejection(&self, stack: &[usize], position: usize, symbol: Symbol) -> Rejection {
+        let state = *stack.last().unwrap();
+        let mut expected: Vec<Symbol> = self
+            .action_table
+            .keys()
+            .filter(|(from, lookahead)| *from == state && *lookahead != Symbol::Error)
+            .map(|(_, lookahead)| *lookahead)
+            .filter(|lookahead| {
+                self.consume(
+                    &mut stack.to_vec(),
+                    *lookahead,
+                    &mut ParseRunStats::default(),
+                )
+                .is_some()
+            })
+            .collect();
+        expected.sort();
+        Rejection {
+            position,
+            found: symbol,
+            expected,
+        }
+    }
+
+    /// Parses an input string, recovering from parse errors with the
+    /// yacc-style `error` token ([`Symbol::Error`], written `\E`).
+    ///
+    /// # Algorithm
+    /// On a parse error, states are popped until one can shift `error`, which
+    /// is then shifted. Input symbols that still have no action are discarded
+    /// silently until one is shifted, which ends the recovery. So with
+    /// `S → A;S | A;` and `A → a | \E`, a bad statement is skipped up to the
+    /// next `;`.
+    ///
+    /// Returns every error recovered from, in input order; an empty list means
+    /// the input is in the language, as with [`parse`](Self::parse). Fails with
+    /// [`GrammarError::Rejected`] for an error no state can recover from, or
+    /// when the input ends during recovery, and with
+    /// [`GrammarError::NonterminalInInput`] as
+    /// [`parse_detailed`](Self::parse_detailed) does.
+    pub fn parse_with_recovery(&self, input: &str) -> Result<Vec<Rejection>> {
+        let mut input_symbols = self.grammar.config().string_to_symbols(input);
+        check_input(&input_symbols)?;
+        let length = input_symbols.len();
+        input_symbols.push(Symbol::EndMarker);
+
+        let mut stack: Vec<usize> = vec![0];
+        let mut stats = ParseRunStats::default();
+        let mut errors: Vec<Rejection> = Vec::new();
+        let mut recovering = false;
+
+        for (position, symbol) in input_symbols.into_iter().enumerate() {
+            let before = stack.clone();
+            match self.consume(&mut stack, symbol, &mut stats) {
+                Some(Action::Accept) => return Ok(errors),
+                Some(_) => {
+                    recovering = false;
+                    continue;
+                }
+                None if recovering => {
+                    // Discard the symbol, undoing the reductions it caused
+                    stack = before;
+                    continue;
+                }
+                None => {}
+            }
+
+            let rejection = self.rejection(&before, position, symbol);
+            stack = before;
+            while let Some(&state) = stack.last() {
+                if let Some(Action::Shift(next)) = self.action_table.get(&(state, Symbol::Error)) {
+                    stack.push(*next);
+                    break;
+                }
+                stack.pop();
+            }
+            if stack.is_empty() {
+                return Err(GrammarError::Rejected(rejection));
+            }
+            errors.push(rejection);
+            recovering = true;
+
+            // Retry the symbol after the error token
+            let before = stack.clone();
+            match self.consume(&mut stack, symbol, &mut stats) {
+                Some(Action::Accept) => return Ok(errors),
+                Some(_) => recovering = false,
+                None => stack = before,
+            }
+        }
+
+        // The end marker was discarded during recovery
+        Err(GrammarError::Rejected(self.rejection(
+            &stack,
+            length,
+            Symbol::EndMarker,
+        )))
+    }
+
+    /// Parses an input string, recording every step of the parse.
+    ///
+    /// Each step shows the stack as states interleaved with the grammar
+    /// symbols between them (`0 i 5`), the remaining input and the action
+    /// taken: `shift n`, `reduce A → α`, `accept` or `error`. The verdict
+    /// agrees with [`parse`](Self::parse).
+    pub fn parse_trace(&self, input: &str) -> ParseTrace {
+        let config = self.grammar.config();
+        let mut input_symbols = config.string_to_symbols(input);
+        input_symbols.push(Symbol::EndMarker);
+
+        let mut states: Vec<usize> = vec![0];
+        let mut symbols: Vec<Symbol> = Vec::new();
         let mut input_index = 0;
+        let mut trace = ParseTrace::default();
 
         loop {
-            if input_index >= input_symbols.len() {
-                return false;
+            let state = *states.last().unwrap();
+            let lookahead = input_symbols[input_index];
+
+            let mut stack_text = states[0].to_string();
+            for (symbol, state) in symbols.iter().zip(&states[1..]) {
+                stack_text.push_str(&format!(" {} {}", config.symbol_to_string(*symbol), state));
             }
+            let input_text: String = input_symbols[input_index..]
+                .iter()
+                .map(|s| config.symbol_to_string(*s))
+                .collect();
 
-            let state = *stack.last().unwrap();
-            let current_symbol = input_symbols[input_index];
-            let key = (state, current_symbol);
+            match self.action_table.get(&(state, lookahead)) {
+                Some(Action::Shift(next_state)) => {
+                    trace.push(stack_text, input_text, format!("shift {}", next_state));
+                    states.push(*next_state);
+                    symbols.push(lookahead);
+                    input_index += 1;
+                }
+                Some(Action::Accept) => {
+                    trace.push(stack_text, input_text, "accept");
+                    trace.accepted = true;
+                    return trace;
+                }
+                Some(Action::Reduce(number)) => {
+                    let production = &self.grammar.all_productions()[number - 1];
+                    let rhs_len = if production.is_epsilon() {
+                        0
+                    } else {
+                        production.rhs.len()
+                    };
+                    states.truncate(states.len() - rhs_len);
+                    symbols.truncate(symbols.len() - rhs_len);
+
+                    let state_after_pop = *states.last().unwrap();
+                    let Some(next_state) = self.goto_table.get(&(state_after_pop, production.lhs))
+                    else {
+                        trace.push(stack_text, input_text, "error");
+                        return trace;
+                    };
+                    trace.push(stack_text, input_text, format!("reduce {}", production));
+                    states.push(*next_state);
+                    symbols.push(production.lhs);
+                }
+                None => {
+                    trace.push(stack_text, input_text, "error");
+                    return trace;
+                }
+            }
+        }
+    }
+
+    /// Checks whether the input is a viable prefix: a prefix of at least one
+    /// string accepted by the grammar.
+    ///
+    /// Unlike [`parse`](Self::parse) no `$` is appended, so `i+` is accepted as
+    /// a prefix of the expression grammar while `+` is not.
+    pub fn accepts_prefix(&self, input: &str) -> bool {
+        self.walk_prefix(input).is_some()
+    }
+
+    /// Returns the terminals (and `$`) that may legally follow the prefix.
+    ///
+    /// These are the symbols with a non-error ACTION entry in the state
+    /// reached after parsing the prefix; the set is empty if the prefix itself
+    /// is not viable.
+    pub fn valid_next(&self, prefix: &str) -> HashSet<Symbol> {
+        let Some(stack) = self.walk_prefix(prefix) else {
+            return HashSet::new();
+        };
+        let state = *stack.last().unwrap();
+
+        self.action_table
+            .keys()
+            .filter(|(from, _)| *from == state)
+            .map(|(_, symbol)| *symbol)
+            .collect()
+    }
 
-            let action = self.action_table.get(&key);
+    /// Returns how many leading input symbols are consumed before the first
+    /// error action.
+    ///
+    /// For a rejected input this points at the offending symbol: `i+i)` on the
+    /// expression grammar yields 3, the position of `)`. An input that is a
+    /// viable prefix yields its full length, whether or not it is accepted.
+    pub fn longest_accepted_prefix(&self, input: &str) -> usize {
+        let mut stack: Vec<usize> = vec![0];
+        let mut stats = ParseRunStats::default();
+
+        self.grammar
+            .config()
+            .string_to_symbols(input)
+            .into_iter()
+            .take_while(|symbol| {
+                matches!(
+                    self.consume(&mut stack, *symbol, &mut stats),
+                    Some(Action::Shift(_))
+                )
+            })
+            .count()
+    }
+
+    /// Runs the shift-reduce loop over every symbol of the prefix and returns
+    /// the resulting state stack, or `None` if some symbol cannot be shifted.
+    fn walk_prefix(&self, prefix: &str) -> Option<Vec<usize>> {
+        let mut stack: Vec<usize> = vec![0];
+        let mut stats = ParseRunStats::default();
+
+        for symbol in self.grammar.config().string_to_symbols(prefix) {
+            match self.consume(&mut stack, symbol, &mut stats)? {
+                Action::Shift(_) => {}
+                _ => return None,
+            }
+        }
+
+        Some(stack)
+    }
+
+    /// Applies reductions with `symbol` as lookahead until it is shifted or
+    /// accepted.
+    ///
+    /// Returns the action that consumed the symbol, or `None` on a parse error.
+    /// Shifts, reductions and the stack depth are recorded in `stats`.
+    fn consume(
+        &self,
+        stack: &mut Vec<usize>,
+        symbol: Symbol,
+        stats: &mut ParseRunStats,
+    ) -> Option<&Action> {
+        self.consume_logged(stack, symbol, stats, None)
+    }
+
+    /// Like [`consume`](Self::consume), also appending every action taken to
+    /// `log` if given.
+    fn consume_logged(
+        &self,
+        stack: &mut Vec<usize>,
+        symbol: Symbol,
+        stats: &mut ParseRunStats,
+        mut log: Option<&mut Vec<Action>>,
+    ) -> Option<&Action> {
+        loop {
+            let state = *stack.last().unwrap();
+            let action = self.action_table.get(&(state, symbol))?;
+            if let Some(log) = log.as_deref_mut() {
+                log.push(action.clone());
+            }
 
             match action {
-                Some(Action::Accept) => return true,
-                Some(Action::Shift(next_state)) => {
-                    // Push symbol and next state
+                Action::Shift(next_state) => {
                     stack.push(*next_state);
-                    symbol_stack.push(current_symbol);
-                    input_index += 1;
+                    stats.shifts += 1;
+                    stats.max_stack_depth = stats.max_stack_depth.max(stack.len());
+                    return Some(action);
                 }
-                Some(Action::Reduce(production)) => {
-                    // Pop |rhs| symbols and states
-                    let rhs_len = if production.rhs == vec![Symbol::Epsilon] {
+                Action::Accept => return Some(action),
+                Action::Reduce(number) => {
+                    let production = &self.grammar.all_productions()[number - 1];
+                    // Pop |rhs| states
+                    let rhs_len = if production.is_epsilon() {
                         0
                     } else {
                         production.rhs.len()
                     };
+                    stack.truncate(stack.len() - rhs_len);
 
-                    for _ in 0..rhs_len {
-                        stack.pop();
-                        symbol_stack.pop();
-                    }
-
-                    // Get state at top of stack after popping
+                    // Find next state via GOTO from the state at top of stack
                     let state_after_pop = *stack.last().unwrap();
+                    let next_state = self.goto_table.get(&(state_after_pop, production.lhs))?;
+                    stack.push(*next_state);
+                    stats.reduces += 1;
+                    stats.max_stack_depth = stats.max_stack_depth.max(stack.len());
+                }
+            }
+        }
+    }
+}
 
-                    // Find next state via GOTO
-                    let goto_key = (state_after_pop, production.lhs);
-                    if let Some(&next_state) = self.goto_table.get(&goto_key) {
-                        stack.push(next_state);
-                        symbol_stack.push(production.lhs);
-                    } else {
-                        return false;
-                    }
+/// Returns the number of a production: its 1-based position in the grammar,
+/// with 0 reserved for the augmented production S' → S.
+fn production_number(grammar: &Grammar, production: &Production) -> usize {
+    grammar.production_index(production).unwrap_or(0)
+}
+
+/// Renders an item as `A → α•β`, writing the augmented start as `S'`.
+fn item_label(grammar: &Grammar, item: &Item) -> String {
+    let lhs = if item.production.lhs == AUGMENTED_START {
+        format!("{}'", grammar.start_symbol())
+    } else {
+        item.production.lhs.to_string()
+    };
+    let body: &[Symbol] = if item.production.is_epsilon() {
+        &[]
+    } else {
+        &item.production.rhs
+    };
+    format!(
+        "{} → {}•{}",
+        lhs,
+        symbols_to_string(&body[..item.dot_position]),
+        symbols_to_string(&body[item.dot_position..])
+    )
+}
+
+/// Returns the items of a state with the kernel items first, then the items
+/// added by closure, each group in production order.
+fn sorted_items<'a>(grammar: &Grammar, state: &'a ItemSet) -> Vec<&'a Item> {
+    let mut items: Vec<&Item> = state.iter().collect();
+    items.sort_by_key(|item| {
+        let is_closure = item.dot_position == 0 && item.production.lhs != AUGMENTED_START;
+        (
+            is_closure,
+            production_number(grammar, &item.production),
+            item.dot_position,
+        )
+    });
+    items
+}
+
+/// Renders an LR(0) automaton in Graphviz DOT format, marking the states that
+/// appear in `conflicts` in red with the symbols they conflict on.
+fn render_dot(
+    grammar: &Grammar,
+    states: &[ItemSet],
+    transitions: &HashMap<(usize, Symbol), usize>,
+    conflicts: &[GrammarError],
+) -> String {
+    let mut output = String::from("digraph LR0 {\n");
+    output.push_str("    rankdir=LR;\n");
+    output.push_str("    node [shape=box];\n");
+
+    for (id, state) in states.iter().enumerate() {
+        let mut label = format!("I{}", id);
+        for item in sorted_items(grammar, state) {
+            label.push_str("\\n");
+            label.push_str(&escape_dot(&item_label(grammar, item)));
+        }
+
+        let mut conflict_symbols: Vec<&str> = conflicts
+            .iter()
+            .filter_map(|conflict| match conflict {
+                GrammarError::SLR1ShiftReduceConflict { state, symbol, .. }
+                | GrammarError::SLR1ReduceReduceConflict { state, symbol, .. }
+                    if *state == id =>
+                {
+                    Some(symbol.as_str())
                 }
-                None => return false,
+                _ => None,
+            })
+            .collect();
+        conflict_symbols.sort();
+        conflict_symbols.dedup();
+
+        if conflict_symbols.is_empty() {
+            output.push_str(&format!("    {} [label=\"{}\"];\n", id, label));
+        } else {
+            for symbol in conflict_symbols {
+                label.push_str(&format!("\\nconflict on {}", escape_dot(symbol)));
             }
+            output.push_str(&format!(
+                "    {} [label=\"{}\", color=red, fontcolor=red];\n",
+                id, label
+            ));
         }
     }
+
+    let mut edges: Vec<(usize, Symbol, usize)> = transitions
+        .iter()
+        .map(|(&(from, symbol), &to)| (from, symbol, to))
+        .collect();
+    edges.sort();
+    for (from, symbol, to) in edges {
+        output.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            from,
+            to,
+            escape_dot(&symbol.to_string())
+        ));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Escapes a string for use inside a double-quoted DOT label.
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Joins the fields into one CSV line, quoting fields that need it.
+fn csv_row(fields: &[String]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    format!("{}\n", quoted.join(","))
 }
@@ -13,6 +13,12 @@ use std::fmt;
 /// - Nonterminals: Uppercase letters (A-Z)
 /// - Epsilon: The empty string, represented by 'e'
 /// - EndMarker: The end-of-input marker '$'
+///
+/// Terminals that cannot be typed in a production are written with the escape
+/// sequences of [`unescape`], and displayed the same way.
+///
+/// [`Symbol::Special`] markers are never produced by grammar parsing; tooling
+/// uses them for synthetic symbols that must not collide with grammar ones.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Symbol {
     /// A terminal symbol (lowercase, digits, or special characters)
@@ -23,6 +29,15 @@ pub enum Symbol {
     Epsilon,
     /// The end-of-input marker ($)
     EndMarker,
+    /// The yacc-style `error` token, written `\E` in a production
+    ///
+    /// It counts as a terminal but never occurs in the input: the SLR(1)
+    /// parser shifts it while recovering from a parse error, see
+    /// [`SLR1Parser::parse_with_recovery`](crate::slr1::SLR1Parser::parse_with_recovery).
+    Error,
+    /// A reserved pseudo-symbol named by its label, such as the augmented
+    /// start of the LR(0) automaton
+    Special(&'static str),
 }
 
 impl Symbol {
@@ -48,10 +63,25 @@ impl Symbol {
         }
     }
 
-    /// Checks if this symbol is a terminal.
+    /// Creates a terminal symbol, regardless of the character's casing.
+    ///
+    /// Use this instead of [`Symbol::from_char`] when the casing convention
+    /// must be overridden (e.g. an uppercase terminal).
+    #[inline]
+    pub const fn terminal(c: char) -> Self {
+        Symbol::Terminal(c)
+    }
+
+    /// Creates a nonterminal symbol, regardless of the character's casing.
+    #[inline]
+    pub const fn nonterminal(c: char) -> Self {
+        Symbol::Nonterminal(c)
+    }
+
+    /// Checks if this symbol is a terminal, including the `error` token.
     #[inline]
     pub const fn is_terminal(&self) -> bool {
-        matches!(self, Symbol::Terminal(_))
+        matches!(self, Symbol::Terminal(_) | Symbol::Error)
     }
 
     /// Checks if this symbol is a nonterminal.
@@ -72,31 +102,60 @@ impl Symbol {
         matches!(self, Symbol::EndMarker)
     }
 
+    /// Checks if this symbol is a reserved [`Symbol::Special`] marker.
+    #[inline]
+    pub const fn is_special(&self) -> bool {
+        matches!(self, Symbol::Special(_))
+    }
+
     /// Returns the character representation of this symbol, if applicable.
     pub const fn as_char(&self) -> Option<char> {
         match self {
             Symbol::Terminal(c) | Symbol::Nonterminal(c) => Some(*c),
-            Symbol::Epsilon | Symbol::EndMarker => None,
+            Symbol::Epsilon | Symbol::EndMarker | Symbol::Error | Symbol::Special(_) => None,
         }
     }
+
+    /// Returns how many terminal columns the symbol takes when displayed.
+    ///
+    /// This is 1 for a plain character, including `ε` and `$`. Special
+    /// markers and escaped terminals take the width of their label or escape
+    /// sequence, measured as [`display_width`] does.
+    pub fn display_width(&self) -> usize {
+        display_width(&self.to_string())
+    }
 }
 
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Symbol::Terminal(c) | Symbol::Nonterminal(c) => write!(f, "{}", c),
+            Symbol::Terminal(c) => match escape(*c) {
+                Some(sequence) => write!(f, "{}", sequence),
+                None => write!(f, "{}", c),
+            },
+            Symbol::Nonterminal(c) => write!(f, "{}", c),
             Symbol::Epsilon => write!(f, "ε"),
             Symbol::EndMarker => write!(f, "$"),
+            Symbol::Error => write!(f, "error"),
+            Symbol::Special(label) => write!(f, "{}", label),
         }
     }
 }
 
 /// Custom ordering for symbols to ensure consistent sorting.
 ///
-/// Order: Epsilon < Terminals < Nonterminals < EndMarker
+/// Order: Epsilon < Terminals < Error < Nonterminals < EndMarker < Special,
+/// with special markers ordered by label. Terminals, and likewise
+/// nonterminals, compare by Unicode code point, so `(` < `*` < `+` < digits
+/// < lowercase letters. Table exporters sort their columns this way, which
+/// puts `$` last.
 impl Ord for Symbol {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
+            (Symbol::Special(l1), Symbol::Special(l2)) => l1.cmp(l2),
+            (Symbol::Special(_), _) => Ordering::Greater,
+            (_, Symbol::Special(_)) => Ordering::Less,
+
             (Symbol::Epsilon, Symbol::Epsilon) => Ordering::Equal,
             (Symbol::Epsilon, _) => Ordering::Less,
             (_, Symbol::Epsilon) => Ordering::Greater,
@@ -105,6 +164,12 @@ impl Ord for Symbol {
             (Symbol::EndMarker, _) => Ordering::Greater,
             (_, Symbol::EndMarker) => Ordering::Less,
 
+            (Symbol::Error, Symbol::Error) => Ordering::Equal,
+            (Symbol::Error, Symbol::Terminal(_)) => Ordering::Greater,
+            (Symbol::Terminal(_), Symbol::Error) => Ordering::Less,
+            (Symbol::Error, Symbol::Nonterminal(_)) => Ordering::Less,
+            (Symbol::Nonterminal(_), Symbol::Error) => Ordering::Greater,
+
             (Symbol::Terminal(c1), Symbol::Terminal(c2)) => c1.cmp(c2),
             (Symbol::Terminal(_), Symbol::Nonterminal(_)) => Ordering::Less,
             (Symbol::Nonterminal(_), Symbol::Terminal(_)) => Ordering::Greater,
@@ -128,3 +193,63 @@ pub fn string_to_symbols(s: &str) -> Vec<Symbol> {
 pub fn symbols_to_string(symbols: &[Symbol]) -> String {
     symbols.iter().map(|s| s.to_string()).collect()
 }
+
+/// Resolves the escape sequences of grammar text, so that whitespace and
+/// control characters can be used as terminals:
+/// - `\n` is a newline and `\t` a tab
+/// - `\s` is a space
+/// - `\\` is a backslash
+///
+/// Any other backslash is kept as it is.
+pub fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars
+            .peek()
+            .copied()
+            .filter(|_| c == '\\')
+            .and_then(resolve_escape)
+        {
+            Some(resolved) => {
+                chars.next();
+                result.push(resolved);
+            }
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns the character an escape sequence `\c` stands for, given `c`.
+pub(crate) fn resolve_escape(c: char) -> Option<char> {
+    match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        's' => Some(' '),
+        '\\' => Some('\\'),
+        _ => None,
+    }
+}
+
+/// Returns the escape sequence a terminal is written and displayed with, if
+/// it needs one; the inverse of [`unescape`].
+fn escape(c: char) -> Option<&'static str> {
+    match c {
+        '\n' => Some("\\n"),
+        '\t' => Some("\\t"),
+        ' ' => Some("\\s"),
+        '\\' => Some("\\\\"),
+        _ => None,
+    }
+}
+
+/// Returns how many terminal columns a string takes when displayed, taken
+/// as one per character.
+///
+/// East Asian wide characters and combining marks are not accounted for:
+/// doing that properly needs the Unicode width tables, which this crate does
+/// not depend on.
+pub fn display_width(s: &str) -> usize {
+    s.chars().count()
+}
@@ -0,0 +1,173 @@
+//! Step-by-step parse traces and rejection reasons.
+//!
+//! A trace records, for every step of a parse, the parser stack, the input
+//! that remains and the action taken. It is rendered as an aligned table, the
+//! way parses are usually worked through by hand. A [`Rejection`] is the short
+//! form: where a rejected input went wrong and what was expected there.
+
+use crate::error::{GrammarError, Result};
+use crate::symbol::{display_width, Symbol};
+use std::fmt;
+
+/// A single step of a parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The parser stack, bottom first.
+    pub stack: String,
+    /// The remaining input, including the end marker.
+    pub input: String,
+    /// The action taken from this configuration.
+    pub action: String,
+}
+
+/// The full trace of a parse, together with its verdict.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTrace {
+    /// Whether the input was accepted.
+    pub accepted: bool,
+    /// The steps taken, in order; the last one is `accept` or `error`.
+    pub steps: Vec<TraceStep>,
+}
+
+impl ParseTrace {
+    /// Records a step.
+    pub(crate) fn push(&mut self, stack: String, input: String, action: impl Into<String>) {
+        self.steps.push(TraceStep {
+            stack,
+            input,
+            action: action.into(),
+        });
+    }
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rows = vec![vec![
+            "Stack".to_string(),
+            "Input".to_string(),
+            "Action".to_string(),
+        ]];
+        rows.extend(
+            self.steps
+                .iter()
+                .map(|step| vec![step.stack.clone(), step.input.clone(), step.action.clone()]),
+        );
+        f.write_str(&render_table(&rows))
+    }
+}
+
+/// Why an input was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    /// Index of the offending symbol in the input; the input length when
+    /// the input ended too early
+    pub position: usize,
+    /// The offending symbol, or the end marker when the input ended too early
+    pub found: Symbol,
+    /// The symbols that would have been accepted at that position, in symbol
+    /// order; the end marker stands for the end of the input
+    pub expected: Vec<Symbol>,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |symbol: &Symbol| match symbol {
+            Symbol::EndMarker => "end of input".to_string(),
+            other => format!("'{}'", other),
+        };
+
+        write!(
+            f,
+            "unexpected {} at position {}",
+            describe(&self.found),
+            self.position
+        )?;
+        if !self.expected.is_empty() {
+            let expected: Vec<String> = self.expected.iter().map(describe).collect();
+            write!(f, "; expected one of: {}", expected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that an input holds only terminals.
+///
+/// Fails with [`GrammarError::NonterminalInInput`] at the first nonterminal,
+/// ε or end marker, which a parser would otherwise report as a plain
+/// mismatch.
+pub(crate) fn check_input(symbols: &[Symbol]) -> Result<()> {
+    match symbols.iter().position(|s| !s.is_terminal()) {
+        Some(position) => Err(GrammarError::NonterminalInInput {
+            position,
+            symbol: symbols[position].to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Renders rows as an aligned text table whose first row is the header.
+///
+/// Columns are separated by ` | ` and padded to their widest cell, measured
+/// by [`display_width`]. The header is underlined with a `-+-` separator row;
+/// trailing padding is trimmed from every line.
+pub(crate) fn render_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .map(|row| display_width(&row[i]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let render = |row: &[String]| -> String {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| {
+                let padding = width - display_width(cell);
+                format!("{}{}", cell, " ".repeat(padding))
+            })
+            .collect();
+        cells.join(" | ").trim_end().to_string()
+    };
+
+    let mut output = String::new();
+    let Some((header, body)) = rows.split_first() else {
+        return output;
+    };
+    output.push_str(&render(header));
+    output.push('\n');
+    let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    output.push_str(&separator.join("-+-"));
+    output.push('\n');
+    for row in body {
+        output.push_str(&render(row));
+        output.push('\n');
+    }
+    output
+}
+
+/// Renders rows as a GitHub-flavored Markdown table whose first row is the
+/// header.
+///
+/// A `|---|` row separates the header from the body; `|` inside a cell is
+/// escaped as `\|`.
+pub(crate) fn render_markdown(rows: &[Vec<String>]) -> String {
+    let render = |row: &[String]| -> String {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut output = String::new();
+    let Some((header, body)) = rows.split_first() else {
+        return output;
+    };
+    output.push_str(&render(header));
+    output.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+    for row in body {
+        output.push_str(&render(row));
+    }
+    output
+}
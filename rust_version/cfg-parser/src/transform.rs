@@ -0,0 +1,972 @@
+//! Grammar transformations.
+//!
+//! This module implements the classic grammar rewrites from Aho et al.,
+//! "Compilers: Principles, Techniques, and Tools" (2nd Edition), section 4.3,
+//! plus a fluent [`GrammarPipeline`] to chain them.
+//!
+//! Every transform returns a new grammar and preserves the start symbol,
+//! except that the normal-form conversions may add a new one for ε.
+
+use crate::error::{GrammarError, Result};
+use crate::first_follow::{
+    compute_first_sets, compute_follow_sets, first_of_string, nullable_symbols,
+};
+use crate::grammar::{Grammar, Production};
+use crate::symbol::Symbol;
+use std::collections::{HashMap, HashSet};
+
+/// Generator of fresh nonterminal names that do not clash with a grammar.
+///
+/// Names are taken from A-Z first, then from the Greek capitals. The
+/// default generator avoids no names.
+#[derive(Debug, Clone, Default)]
+pub struct NameGen {
+    used: HashSet<Symbol>,
+}
+
+impl NameGen {
+    /// Creates a generator avoiding every nonterminal of the grammar.
+    pub fn new(grammar: &Grammar) -> Self {
+        Self {
+            used: grammar.nonterminals().clone(),
+        }
+    }
+
+    /// Marks a nonterminal as used, so it is never returned by
+    /// [`fresh`](Self::fresh).
+    pub fn reserve(&mut self, symbol: Symbol) {
+        self.used.insert(symbol);
+    }
+
+    /// Returns a nonterminal not used so far and marks it as used.
+    pub fn fresh(&mut self) -> Result<Symbol> {
+        let fresh = ('A'..='Z')
+            .chain('Α'..='Ω')
+            .filter(|c| c.is_uppercase())
+            .map(Symbol::Nonterminal)
+            .find(|s| !self.used.contains(s))
+            .ok_or_else(|| {
+                GrammarError::InvalidFormat("Ran out of fresh nonterminal names".to_string())
+            })?;
+        self.used.insert(fresh);
+        Ok(fresh)
+    }
+}
+
+/// Returns the nonterminals in order of first appearance as a left-hand side.
+fn nonterminal_order(productions: &[Production]) -> Vec<Symbol> {
+    let mut order = Vec::new();
+    for production in productions {
+        if !order.contains(&production.lhs) {
+            order.push(production.lhs);
+        }
+    }
+    order
+}
+
+/// Returns the body of a production, treating `A → ε` as an empty body.
+fn body(production: &Production) -> &[Symbol] {
+    if production.is_epsilon() {
+        &[]
+    } else {
+        &production.rhs
+    }
+}
+
+/// Builds a production from a body, using ε for an empty body.
+fn production_from_body(lhs: Symbol, body: Vec<Symbol>) -> Production {
+    if body.is_empty() {
+        Production::new(lhs, vec![Symbol::Epsilon])
+    } else {
+        Production::new(lhs, body)
+    }
+}
+
+/// Pushes a production unless an identical one is already present.
+fn push_unique(productions: &mut Vec<Production>, production: Production) {
+    if !productions.contains(&production) {
+        productions.push(production);
+    }
+}
+
+/// Returns the productive nonterminals: those deriving at least one string
+/// of terminals.
+///
+/// A nonterminal is productive if some production has only terminals, ε,
+/// and productive nonterminals (fixed-point iteration).
+pub(crate) fn productive_nonterminals(grammar: &Grammar) -> HashSet<Symbol> {
+    let mut productive: HashSet<Symbol> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in grammar.all_productions() {
+            if !productive.contains(&production.lhs)
+                && production
+                    .rhs
+                    .iter()
+                    .all(|s| !s.is_nonterminal() || productive.contains(s))
+            {
+                productive.insert(production.lhs);
+                changed = true;
+            }
+        }
+    }
+    productive
+}
+
+/// Removes unproductive and unreachable symbols.
+///
+/// # Algorithm
+/// 1. A nonterminal is productive if some production has only terminals,
+///    ε, and productive nonterminals (fixed-point iteration)
+/// 2. Drop every production that mentions an unproductive nonterminal
+/// 3. Drop every production whose left-hand side is unreachable from the start
+pub fn remove_useless_symbols(grammar: &Grammar) -> Result<Grammar> {
+    // Step 1: productive nonterminals
+    let productive = productive_nonterminals(grammar);
+
+    // Step 2: keep productions over productive symbols
+    let productive_productions: Vec<&Production> = grammar
+        .all_productions()
+        .iter()
+        .filter(|p| {
+            productive.contains(&p.lhs)
+                && p.rhs
+                    .iter()
+                    .all(|s| !s.is_nonterminal() || productive.contains(s))
+        })
+        .collect();
+
+    // Step 3: reachable nonterminals
+    let mut reachable: HashSet<Symbol> = HashSet::from([grammar.start_symbol()]);
+    let mut worklist = vec![grammar.start_symbol()];
+    while let Some(nt) = worklist.pop() {
+        for production in productive_productions.iter().filter(|p| p.lhs == nt) {
+            for symbol in &production.rhs {
+                if symbol.is_nonterminal() && reachable.insert(*symbol) {
+                    worklist.push(*symbol);
+                }
+            }
+        }
+    }
+
+    let productions = productive_productions
+        .into_iter()
+        .filter(|p| reachable.contains(&p.lhs))
+        .cloned()
+        .collect();
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Removes ε-productions.
+///
+/// # Algorithm
+/// For each production A → X₁...Xₙ, add every variant obtained by omitting
+/// any subset of the nullable Xᵢ, except the empty body. ε-productions are
+/// dropped, so the resulting language is L(G) - {ε}.
+pub fn remove_epsilon_productions(grammar: &Grammar) -> Result<Grammar> {
+    let nullable = nullable_symbols(grammar);
+    let mut productions = Vec::new();
+
+    for production in grammar.all_productions() {
+        let mut variants: Vec<Vec<Symbol>> = vec![Vec::new()];
+        for symbol in body(production) {
+            let mut next = Vec::new();
+            for variant in &variants {
+                let mut with = variant.clone();
+                with.push(*symbol);
+                next.push(with);
+                if nullable.contains(symbol) {
+                    next.push(variant.clone());
+                }
+            }
+            variants = next;
+        }
+
+        for variant in variants {
+            if !variant.is_empty() {
+                push_unique(&mut productions, Production::new(production.lhs, variant));
+            }
+        }
+    }
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Removes unit productions (A → B).
+///
+/// # Algorithm
+/// For each nonterminal A, compute the unit closure {B | A ⇒* B using only
+/// unit productions}, then give A every non-unit production of each such B.
+pub fn remove_unit_productions(grammar: &Grammar) -> Result<Grammar> {
+    let mut productions = Vec::new();
+
+    for nt in nonterminal_order(grammar.all_productions()) {
+        let mut closure = vec![nt];
+        let mut index = 0;
+        while index < closure.len() {
+            for production in grammar.get_productions(closure[index]) {
+                if production.is_unit() && !closure.contains(&production.rhs[0]) {
+                    closure.push(production.rhs[0]);
+                }
+            }
+            index += 1;
+        }
+
+        for member in closure {
+            for production in grammar.get_productions(member) {
+                if !production.is_unit() {
+                    push_unique(
+                        &mut productions,
+                        Production::new(nt, production.rhs.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Eliminates left recursion.
+///
+/// # Algorithm
+/// Order the nonterminals A₁...Aₙ. For each Aᵢ:
+/// 1. For each j < i, replace Aᵢ → Aⱼγ by Aᵢ → δγ for every Aⱼ → δ
+/// 2. Replace the immediate left recursion Aᵢ → Aᵢα₁ | ... | β₁ | ... by
+///    Aᵢ → β₁Aᵢ' | ... and Aᵢ' → α₁Aᵢ' | ... | ε
+///
+/// As in the textbook, the result is guaranteed free of left recursion when
+/// the input has no cycles (A ⇒⁺ A) and no ε-productions.
+pub fn eliminate_left_recursion(grammar: &Grammar) -> Result<Grammar> {
+    let order = nonterminal_order(grammar.all_productions());
+    let mut names = NameGen::new(grammar);
+    let mut bodies: HashMap<Symbol, Vec<Vec<Symbol>>> = HashMap::new();
+    let mut tails: HashMap<Symbol, (Symbol, Vec<Vec<Symbol>>)> = HashMap::new();
+
+    for (i, &ai) in order.iter().enumerate() {
+        let mut current: Vec<Vec<Symbol>> = grammar
+            .get_productions(ai)
+            .iter()
+            .map(|p| body(p).to_vec())
+            .collect();
+
+        // Step 1: substitute earlier nonterminals in leading position
+        for &aj in &order[..i] {
+            let mut substituted = Vec::new();
+            for alternative in current {
+                if alternative.first() == Some(&aj) {
+                    for delta in &bodies[&aj] {
+                        let mut new_body = delta.clone();
+                        new_body.extend_from_slice(&alternative[1..]);
+                        if !substituted.contains(&new_body) {
+                            substituted.push(new_body);
+                        }
+                    }
+                } else if !substituted.contains(&alternative) {
+                    substituted.push(alternative);
+                }
+            }
+            current = substituted;
+        }
+
+        // Step 2: eliminate immediate left recursion
+        let (recursive, others): (Vec<Vec<Symbol>>, Vec<Vec<Symbol>>) = current
+            .into_iter()
+            .partition(|alternative| alternative.first() == Some(&ai));
+
+        if recursive.is_empty() {
+            bodies.insert(ai, others);
+            continue;
+        }
+
+        let tail = names.fresh()?;
+        let new_bodies = others
+            .into_iter()
+            .map(|mut beta| {
+                beta.push(tail);
+                beta
+            })
+            .collect();
+        let mut tail_bodies: Vec<Vec<Symbol>> = recursive
+            .into_iter()
+            .filter(|alpha| alpha.len() > 1)
+            .map(|alpha| {
+                let mut new_body = alpha[1..].to_vec();
+                new_body.push(tail);
+                new_body
+            })
+            .collect();
+        tail_bodies.push(Vec::new());
+
+        bodies.insert(ai, new_bodies);
+        tails.insert(ai, (tail, tail_bodies));
+    }
+
+    let mut productions = Vec::new();
+    for nt in &order {
+        for new_body in &bodies[nt] {
+            push_unique(
+                &mut productions,
+                production_from_body(*nt, new_body.clone()),
+            );
+        }
+        if let Some((tail, tail_bodies)) = tails.get(nt) {
+            for new_body in tail_bodies {
+                push_unique(
+                    &mut productions,
+                    production_from_body(*tail, new_body.clone()),
+                );
+            }
+        }
+    }
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Upper bound on the leading nonterminals [`left_factor`] expands, which
+/// keeps it finite on grammars that no amount of expansion makes LL(1).
+const MAX_EXPANSIONS: usize = 64;
+
+/// Left-factors the grammar.
+///
+/// # Algorithm
+/// 1. While some nonterminal A has two or more alternatives starting with
+///    the same symbol, take the longest common prefix α of those alternatives
+///    and replace A → αβ₁ | ... | αβₙ by A → αA' and A' → β₁ | ... | βₙ.
+///    If β₁ | ... | βₙ are exactly the alternatives of a nonterminal B, now
+///    or before some expansion of step 2, B is used as A' instead.
+/// 2. If an alternative Xβ of A starts with a nonterminal X that does not
+///    left-derive A, and either FIRST(Xβ) meets the FIRST set of another
+///    alternative of A or X is nullable and has an LL(1) conflict of its own,
+///    replace Xβ by γβ for every X → γ and go back to step 1. A unit
+///    alternative X of a nonterminal added by step 1 is expanded as well, so
+///    factoring introduces no unit productions.
+///
+/// Step 2 uncovers prefixes hidden behind nonterminals, such as those left
+/// by [`remove_unit_productions`]; it is applied at most a fixed number of
+/// times, so left-recursive grammars are returned merely factored.
+pub fn left_factor(grammar: &Grammar) -> Result<Grammar> {
+    let mut names = NameGen::new(grammar);
+    let mut productions: Vec<Production> = grammar.all_productions().to_vec();
+    let mut aliases: HashMap<Vec<Vec<Symbol>>, Symbol> = HashMap::new();
+    let mut tails = HashSet::new();
+
+    for _ in 0..=MAX_EXPANSIONS {
+        while let Some((lhs, prefix)) = find_common_prefix(&productions) {
+            let suffixes: Vec<Vec<Symbol>> = productions
+                .iter()
+                .filter(|p| p.lhs == lhs && body(p).starts_with(&prefix))
+                .map(|p| body(p)[prefix.len()..].to_vec())
+                .collect();
+            let key = body_set(suffixes.iter().cloned());
+            let existing = aliases.get(&key).copied().or_else(|| {
+                nonterminal_order(&productions)
+                    .into_iter()
+                    .find(|&nt| alternatives_key(&productions, nt) == key)
+            });
+            let tail = match existing {
+                Some(tail) => tail,
+                None => {
+                    let fresh = names.fresh()?;
+                    tails.insert(fresh);
+                    fresh
+                }
+            };
+
+            let mut factored = Vec::new();
+            let mut head_added = false;
+            for production in productions {
+                if production.lhs == lhs && body(&production).starts_with(&prefix) {
+                    if !head_added {
+                        let mut head = prefix.clone();
+                        head.push(tail);
+                        factored.push(Production::new(lhs, head));
+                        head_added = true;
+                    }
+                } else {
+                    factored.push(production);
+                }
+            }
+            if existing.is_none() {
+                for suffix in suffixes {
+                    push_unique(&mut factored, production_from_body(tail, suffix));
+                }
+            }
+            productions = factored;
+        }
+
+        let current = grammar.clone_with_productions(productions.clone())?;
+        let Some(index) = find_expansion(&current, &tails) else {
+            break;
+        };
+        // The alternatives as they are now keep denoting the nonterminal
+        aliases
+            .entry(alternatives_key(&productions, productions[index].lhs))
+            .or_insert(productions[index].lhs);
+        let production = productions.remove(index);
+        let (&leading, rest) = production
+            .rhs
+            .split_first()
+            .expect("expanded bodies are non-empty");
+        let expanded: Vec<Production> = productions
+            .iter()
+            .filter(|p| p.lhs == leading)
+            .map(|p| production_from_body(production.lhs, [body(p), rest].concat()))
+            .collect();
+        for (offset, new_production) in expanded.into_iter().enumerate() {
+            if !productions.contains(&new_production) {
+                productions.insert(index + offset, new_production);
+            }
+        }
+    }
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Returns a set of bodies in a canonical form, for comparing the
+/// alternatives of two nonterminals.
+fn body_set(bodies: impl Iterator<Item = Vec<Symbol>>) -> Vec<Vec<Symbol>> {
+    let mut set: Vec<Vec<Symbol>> = bodies.collect();
+    set.sort();
+    set.dedup();
+    set
+}
+
+/// Returns the alternatives of a nonterminal as a canonical set of bodies.
+fn alternatives_key(productions: &[Production], nt: Symbol) -> Vec<Vec<Symbol>> {
+    body_set(
+        productions
+            .iter()
+            .filter(|p| p.lhs == nt)
+            .map(|p| body(p).to_vec()),
+    )
+}
+
+/// Returns, for every nonterminal, the nonterminals it can derive in leading
+/// position, looking past nullable symbols.
+fn left_corners(grammar: &Grammar) -> HashMap<Symbol, HashSet<Symbol>> {
+    let nullable = nullable_symbols(grammar);
+    let mut corners: HashMap<Symbol, HashSet<Symbol>> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in grammar.all_productions() {
+            let mut found = HashSet::new();
+            for symbol in body(production) {
+                if !symbol.is_nonterminal() {
+                    break;
+                }
+                found.insert(*symbol);
+                found.extend(corners.get(symbol).cloned().unwrap_or_default());
+                if !nullable.contains(symbol) {
+                    break;
+                }
+            }
+            let entry = corners.entry(production.lhs).or_default();
+            let before = entry.len();
+            entry.extend(found);
+            changed |= entry.len() != before;
+        }
+    }
+    corners
+}
+
+/// Finds the index of the first production whose leading nonterminal
+/// [`left_factor`] should expand, see step 2 of its algorithm.
+fn find_expansion(grammar: &Grammar, tails: &HashSet<Symbol>) -> Option<usize> {
+    let first_sets = compute_first_sets(grammar);
+    let follow_sets = compute_follow_sets(grammar, &first_sets);
+    let nullable = nullable_symbols(grammar);
+    let corners = left_corners(grammar);
+    let productions = grammar.all_productions();
+    let first_of = |production: &Production| -> HashSet<Symbol> {
+        let mut first = first_of_string(&first_sets, body(production));
+        first.remove(&Symbol::Epsilon);
+        first
+    };
+    let has_conflict = |nt: Symbol| {
+        let firsts: Vec<HashSet<Symbol>> =
+            grammar.get_productions(nt).iter().map(first_of).collect();
+        let overlapping = firsts
+            .iter()
+            .enumerate()
+            .any(|(i, a)| firsts[i + 1..].iter().any(|b| !a.is_disjoint(b)));
+        overlapping
+            || firsts
+                .iter()
+                .any(|first| !first.is_disjoint(follow_sets.follow_of(nt)))
+    };
+
+    for nt in nonterminal_order(productions) {
+        for (index, production) in productions.iter().enumerate() {
+            let Some(&leading) = body(production).first() else {
+                continue;
+            };
+            if production.lhs != nt
+                || !leading.is_nonterminal()
+                || leading == nt
+                || corners.get(&leading).is_some_and(|c| c.contains(&nt))
+            {
+                continue;
+            }
+            let first = first_of(production);
+            let overlaps = productions.iter().enumerate().any(|(other, p)| {
+                other != index && p.lhs == nt && !first.is_disjoint(&first_of(p))
+            });
+            let unit_tail = tails.contains(&nt) && production.rhs.len() == 1;
+            if overlaps || unit_tail || (nullable.contains(&leading) && has_conflict(leading)) {
+                return Some(index);
+            }
+        }
+    }
+    None
+}
+
+/// Finds the first nonterminal with alternatives sharing a leading symbol,
+/// and the longest prefix common to all of those alternatives.
+fn find_common_prefix(productions: &[Production]) -> Option<(Symbol, Vec<Symbol>)> {
+    for nt in nonterminal_order(productions) {
+        let bodies: Vec<&[Symbol]> = productions
+            .iter()
+            .filter(|p| p.lhs == nt)
+            .map(body)
+            .collect();
+
+        for (i, first_body) in bodies.iter().enumerate() {
+            let Some(first) = first_body.first() else {
+                continue;
+            };
+            let group: Vec<&[Symbol]> = bodies[i..]
+                .iter()
+                .filter(|b| b.first() == Some(first))
+                .copied()
+                .collect();
+            if group.len() < 2 {
+                continue;
+            }
+
+            let mut prefix_len = 1;
+            while group
+                .iter()
+                .all(|b| b.len() > prefix_len && b[prefix_len] == group[0][prefix_len])
+            {
+                prefix_len += 1;
+            }
+            return Some((nt, group[0][..prefix_len].to_vec()));
+        }
+    }
+    None
+}
+
+/// Right-factors the grammar, the mirror image of [`left_factor`].
+///
+/// # Algorithm
+/// While some nonterminal A has two or more alternatives ending with the
+/// same symbol, take the longest common suffix γ of those alternatives and
+/// replace A → β₁γ | ... | βₙγ by A → A'γ and A' → β₁ | ... | βₙ.
+pub fn right_factor(grammar: &Grammar) -> Result<Grammar> {
+    let mut names = NameGen::new(grammar);
+    let mut productions: Vec<Production> = grammar.all_productions().to_vec();
+
+    while let Some((lhs, suffix)) = find_common_suffix(&productions) {
+        let head = names.fresh()?;
+        let mut factored = Vec::new();
+        let mut head_productions = Vec::new();
+        for production in productions {
+            let production_body = body(&production);
+            if production.lhs == lhs && production_body.ends_with(&suffix) {
+                if head_productions.is_empty() {
+                    let mut tail = vec![head];
+                    tail.extend_from_slice(&suffix);
+                    factored.push(Production::new(lhs, tail));
+                }
+                let prefix = production_body[..production_body.len() - suffix.len()].to_vec();
+                push_unique(&mut head_productions, production_from_body(head, prefix));
+            } else {
+                factored.push(production);
+            }
+        }
+        factored.extend(head_productions);
+        productions = factored;
+    }
+
+    grammar.clone_with_productions(productions)
+}
+
+/// Finds the first nonterminal with alternatives sharing a trailing symbol,
+/// and the longest suffix common to all of those alternatives.
+fn find_common_suffix(productions: &[Production]) -> Option<(Symbol, Vec<Symbol>)> {
+    for nt in nonterminal_order(productions) {
+        let bodies: Vec<&[Symbol]> = productions
+            .iter()
+            .filter(|p| p.lhs == nt)
+            .map(body)
+            .collect();
+
+        for (i, first_body) in bodies.iter().enumerate() {
+            let Some(last) = first_body.last() else {
+                continue;
+            };
+            let group: Vec<&[Symbol]> = bodies[i..]
+                .iter()
+                .filter(|b| b.last() == Some(last))
+                .copied()
+                .collect();
+            if group.len() < 2 {
+                continue;
+            }
+
+            let from_end = |b: &[Symbol], k: usize| b[b.len() - 1 - k];
+            let mut suffix_len = 1;
+            while group.iter().all(|b| {
+                b.len() > suffix_len && from_end(b, suffix_len) == from_end(group[0], suffix_len)
+            }) {
+                suffix_len += 1;
+            }
+            let first = group[0];
+            return Some((nt, first[first.len() - suffix_len..].to_vec()));
+        }
+    }
+    None
+}
+
+/// Converts the grammar to Chomsky normal form.
+///
+/// # Algorithm
+/// 1. Remove ε-productions, unit productions and useless symbols
+/// 2. In every body of length two or more, replace each terminal a by a new
+///    nonterminal Tₐ → a
+/// 3. Split every body X₁X₂...Xₙ with n > 2 into X₁B with B → X₂...Xₙ,
+///    reusing the same B for the same pair of trailing symbols
+/// 4. If ε is in the language, add S → ε; when S occurs on a right-hand side
+///    a new start symbol S₀ → α | ε is introduced for every S → α
+///
+/// The result satisfies [`Grammar::is_cnf`]; only step 4 can change the start
+/// symbol.
+pub fn to_cnf(grammar: &Grammar) -> Result<Grammar> {
+    let derives_epsilon = nullable_symbols(grammar).contains(&grammar.start_symbol());
+    let reduced = normal_form_base(grammar)?;
+    let mut names = NameGen::new(&reduced);
+    let mut terminal_names = HashMap::new();
+    let mut pair_names: HashMap<Vec<Symbol>, Symbol> = HashMap::new();
+    let mut productions = Vec::new();
+    let mut helpers = Vec::new();
+
+    for production in reduced.all_productions() {
+        if production.rhs.len() == 1 {
+            push_unique(&mut productions, production.clone());
+            continue;
+        }
+
+        // Step 2: terminals only stand alone
+        let mut rhs = Vec::new();
+        for symbol in &production.rhs {
+            if symbol.is_terminal() {
+                rhs.push(terminal_name(
+                    *symbol,
+                    &mut names,
+                    &mut terminal_names,
+                    &mut helpers,
+                )?);
+            } else {
+                rhs.push(*symbol);
+            }
+        }
+
+        // Step 3: binarize from the right
+        while rhs.len() > 2 {
+            let pair = rhs.split_off(rhs.len() - 2);
+            let name = match pair_names.get(&pair) {
+                Some(name) => *name,
+                None => {
+                    let name = names.fresh()?;
+                    helpers.push(Production::new(name, pair.clone()));
+                    pair_names.insert(pair, name);
+                    name
+                }
+            };
+            rhs.push(name);
+        }
+        push_unique(&mut productions, Production::new(production.lhs, rhs));
+    }
+    productions.extend(helpers);
+
+    with_start_epsilon(&reduced, productions, derives_epsilon, &mut names)
+}
+
+/// Converts the grammar to Greibach normal form.
+///
+/// # Algorithm
+/// 1. Remove ε-productions, unit productions and useless symbols
+/// 2. Order the nonterminals A₁...Aₙ and, as in
+///    [`eliminate_left_recursion`], substitute earlier nonterminals in
+///    leading position; immediate left recursion Aᵢ → Aᵢα | β is replaced
+///    by Aᵢ → β | βZ and Z → α | αZ, without ε-productions
+/// 3. Substitute leading nonterminals until every body starts with a
+///    terminal
+/// 4. Replace each non-leading terminal a by a new nonterminal Tₐ → a
+/// 5. If ε is in the language, add the start ε-production as in [`to_cnf`]
+///
+/// The result satisfies [`Grammar::is_gnf`]; only step 5 can change the start
+/// symbol. Step 3 may multiply the number of productions.
+pub fn to_gnf(grammar: &Grammar) -> Result<Grammar> {
+    let derives_epsilon = nullable_symbols(grammar).contains(&grammar.start_symbol());
+    let reduced = normal_form_base(grammar)?;
+    let mut names = NameGen::new(&reduced);
+    let original = nonterminal_order(reduced.all_productions());
+    let mut order = original.clone();
+    let mut bodies: HashMap<Symbol, Vec<Vec<Symbol>>> = HashMap::new();
+
+    // Step 2: no nonterminal leads to itself or an earlier one
+    for (i, &ai) in original.iter().enumerate() {
+        let mut current: Vec<Vec<Symbol>> = reduced
+            .get_productions(ai)
+            .iter()
+            .map(|p| p.rhs.clone())
+            .collect();
+        for &aj in &original[..i] {
+            current = substitute_leading(current, aj, &bodies[&aj]);
+        }
+
+        let (recursive, others): (Vec<Vec<Symbol>>, Vec<Vec<Symbol>>) = current
+            .into_iter()
+            .partition(|alternative| alternative.first() == Some(&ai));
+        if recursive.is_empty() {
+            bodies.insert(ai, others);
+            continue;
+        }
+
+        let tail = names.fresh()?;
+        let alphas: Vec<Vec<Symbol>> = recursive
+            .into_iter()
+            .map(|alpha| alpha[1..].to_vec())
+            .collect();
+        bodies.insert(ai, with_tail(others, tail));
+        bodies.insert(tail, with_tail(alphas, tail));
+        order.push(tail);
+    }
+
+    // Step 3: every body starts with a terminal
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for nt in &order {
+            let leading: Vec<Symbol> = bodies[nt]
+                .iter()
+                .filter_map(|b| b.first().copied().filter(|s| s.is_nonterminal()))
+                .collect();
+            for symbol in leading {
+                let replacements = bodies[&symbol].clone();
+                let current = bodies.remove(nt).unwrap_or_default();
+                bodies.insert(*nt, substitute_leading(current, symbol, &replacements));
+                changed = true;
+            }
+        }
+    }
+
+    // Step 4: only nonterminals after the leading terminal
+    let mut terminal_names = HashMap::new();
+    let mut helpers = Vec::new();
+    let mut productions = Vec::new();
+    for nt in &order {
+        for gnf_body in &bodies[nt] {
+            let mut rhs = vec![gnf_body[0]];
+            for symbol in &gnf_body[1..] {
+                if symbol.is_terminal() {
+                    rhs.push(terminal_name(
+                        *symbol,
+                        &mut names,
+                        &mut terminal_names,
+                        &mut helpers,
+                    )?);
+                } else {
+                    rhs.push(*symbol);
+                }
+            }
+            push_unique(&mut productions, Production::new(*nt, rhs));
+        }
+    }
+    productions.extend(helpers);
+
+    with_start_epsilon(&reduced, productions, derives_epsilon, &mut names)
+}
+
+/// Removes ε-productions, unit productions and useless symbols, the common
+/// first step of the normal-form conversions.
+fn normal_form_base(grammar: &Grammar) -> Result<Grammar> {
+    GrammarPipeline::new(grammar.clone())
+        .remove_epsilon()
+        .remove_unit()
+        .remove_useless()
+        .build()
+}
+
+/// Returns the nonterminal standing for a terminal, creating Tₐ → a on first
+/// use.
+fn terminal_name(
+    terminal: Symbol,
+    names: &mut NameGen,
+    terminal_names: &mut HashMap<Symbol, Symbol>,
+    helpers: &mut Vec<Production>,
+) -> Result<Symbol> {
+    if let Some(name) = terminal_names.get(&terminal) {
+        return Ok(*name);
+    }
+    let name = names.fresh()?;
+    helpers.push(Production::new(name, vec![terminal]));
+    terminal_names.insert(terminal, name);
+    Ok(name)
+}
+
+/// Replaces every alternative Bγ by δγ for each replacement δ of B.
+fn substitute_leading(
+    alternatives: Vec<Vec<Symbol>>,
+    target: Symbol,
+    replacements: &[Vec<Symbol>],
+) -> Vec<Vec<Symbol>> {
+    let mut substituted = Vec::new();
+    for alternative in alternatives {
+        if alternative.first() == Some(&target) {
+            for delta in replacements {
+                let mut new_body = delta.clone();
+                new_body.extend_from_slice(&alternative[1..]);
+                if !substituted.contains(&new_body) {
+                    substituted.push(new_body);
+                }
+            }
+        } else if !substituted.contains(&alternative) {
+            substituted.push(alternative);
+        }
+    }
+    substituted
+}
+
+/// Returns the alternatives both as given and followed by `tail`.
+fn with_tail(alternatives: Vec<Vec<Symbol>>, tail: Symbol) -> Vec<Vec<Symbol>> {
+    let mut result = alternatives.clone();
+    result.extend(alternatives.into_iter().map(|mut alternative| {
+        alternative.push(tail);
+        alternative
+    }));
+    result
+}
+
+/// Adds the start ε-production when the language contains ε.
+///
+/// If the start symbol occurs on a right-hand side, a new start symbol is
+/// introduced with a copy of its alternatives so that the ε-production does
+/// not leak into other derivations.
+fn with_start_epsilon(
+    grammar: &Grammar,
+    mut productions: Vec<Production>,
+    derives_epsilon: bool,
+    names: &mut NameGen,
+) -> Result<Grammar> {
+    let start = grammar.start_symbol();
+    if !derives_epsilon {
+        return grammar.clone_with_productions(productions);
+    }
+
+    if productions.iter().all(|p| !p.rhs.contains(&start)) {
+        productions.push(production_from_body(start, Vec::new()));
+        return grammar.clone_with_productions(productions);
+    }
+
+    let new_start = names.fresh()?;
+    let mut with_new_start: Vec<Production> = productions
+        .iter()
+        .filter(|p| p.lhs == start)
+        .map(|p| Production::new(new_start, p.rhs.clone()))
+        .collect();
+    with_new_start.push(production_from_body(new_start, Vec::new()));
+    with_new_start.extend(productions);
+    let mut result = grammar.clone_with_productions(with_new_start)?;
+    result.set_start_symbol(new_start)?;
+    Ok(result)
+}
+
+/// A fluent chain of grammar transformations.
+///
+/// Each step consumes and returns the pipeline; the first failing step is
+/// remembered and reported by [`GrammarPipeline::build`].
+///
+/// # Examples
+/// ```
+/// use cfg_parser::grammar::Grammar;
+/// use cfg_parser::transform::GrammarPipeline;
+///
+/// let lines = vec!["1".to_string(), "S -> Sa b".to_string()];
+/// let grammar = Grammar::parse(&lines).unwrap();
+/// let result = GrammarPipeline::new(grammar)
+///     .eliminate_left_recursion()
+///     .left_factor()
+///     .build()
+///     .unwrap();
+/// assert_eq!(result.all_productions().len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct GrammarPipeline {
+    grammar: Result<Grammar>,
+}
+
+impl GrammarPipeline {
+    /// Starts a pipeline from a grammar.
+    pub fn new(grammar: Grammar) -> Self {
+        Self {
+            grammar: Ok(grammar),
+        }
+    }
+
+    fn apply(self, transform: fn(&Grammar) -> Result<Grammar>) -> Self {
+        Self {
+            grammar: self.grammar.and_then(|g| transform(&g)),
+        }
+    }
+
+    /// Removes unproductive and unreachable symbols.
+    pub fn remove_useless(self) -> Self {
+        self.apply(remove_useless_symbols)
+    }
+
+    /// Removes ε-productions.
+    pub fn remove_epsilon(self) -> Self {
+        self.apply(remove_epsilon_productions)
+    }
+
+    /// Removes unit productions.
+    pub fn remove_unit(self) -> Self {
+        self.apply(remove_unit_productions)
+    }
+
+    /// Eliminates left recursion.
+    pub fn eliminate_left_recursion(self) -> Self {
+        self.apply(eliminate_left_recursion)
+    }
+
+    /// Left-factors the grammar.
+    pub fn left_factor(self) -> Self {
+        self.apply(left_factor)
+    }
+
+    /// Right-factors the grammar.
+    pub fn right_factor(self) -> Self {
+        self.apply(right_factor)
+    }
+
+    /// Converts the grammar to Chomsky normal form.
+    pub fn to_cnf(self) -> Self {
+        self.apply(to_cnf)
+    }
+
+    /// Converts the grammar to Greibach normal form.
+    pub fn to_gnf(self) -> Self {
+        self.apply(to_gnf)
+    }
+
+    /// Returns the transformed grammar, or the first error encountered.
+    pub fn build(self) -> Result<Grammar> {
+        self.grammar
+    }
+}
@@ -0,0 +1,208 @@
+//! Unit tests for the IO-free analysis API
+
+use cfg_parser::analysis::*;
+use cfg_parser::grammar::Grammar;
+use cfg_parser::symbol::Symbol;
+use std::collections::HashSet;
+
+#[test]
+fn test_analyze_example1_slr1_only() {
+    let text = "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\n";
+    let result = analyze_text(text, &["i+i", "(i)", "(i+i)*i)"]).unwrap();
+
+    assert_eq!(result.classification, Classification::SLR1Only);
+    let verdicts: Vec<Option<bool>> = result.verdicts.iter().map(|v| v.accepted).collect();
+    assert_eq!(verdicts, vec![Some(true), Some(true), Some(false)]);
+    assert_eq!(result.verdicts[2].input, "(i+i)*i)");
+}
+
+#[test]
+fn test_analyze_example2_both() {
+    let text = "3\nS -> AB\nA -> aA d\nB -> bBc e\n";
+    let result = analyze_text(text, &["d", "adbc", "a"]).unwrap();
+
+    assert_eq!(result.classification, Classification::Both);
+    let verdicts: Vec<Option<bool>> = result.verdicts.iter().map(|v| v.accepted).collect();
+    assert_eq!(verdicts, vec![Some(true), Some(true), Some(false)]);
+
+    assert_eq!(
+        result.first_sets,
+        vec![
+            ("A".to_string(), "{a, d}".to_string()),
+            ("B".to_string(), "{ε, b}".to_string()),
+            ("S".to_string(), "{a, d}".to_string()),
+        ]
+    );
+    assert_eq!(
+        result.follow_sets,
+        vec![
+            ("A".to_string(), "{b, $}".to_string()),
+            ("B".to_string(), "{c, $}".to_string()),
+            ("S".to_string(), "{$}".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_analyze_example3_neither() {
+    let text = "2\nS -> A\nA -> A b\n";
+    let result = analyze_text(text, &["b"]).unwrap();
+
+    assert_eq!(result.classification, Classification::Neither);
+    assert_eq!(result.verdicts[0].accepted, None);
+}
+
+#[test]
+fn test_analyze_invalid_grammar_text() {
+    assert!(analyze_text("", &[]).is_err());
+    assert!(analyze_text("x\nS -> a", &[]).is_err());
+}
+
+#[test]
+fn test_classification_display() {
+    assert_eq!(Classification::Both.to_string(), "LL(1) and SLR(1)");
+    assert_eq!(
+        Classification::Neither.to_string(),
+        "neither LL(1) nor SLR(1)"
+    );
+    assert!(Classification::Both.is_ll1() && Classification::Both.is_slr1());
+    assert!(!Classification::SLR1Only.is_ll1());
+}
+
+#[test]
+fn test_left_recursion_cycles_direct() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(
+        left_recursion_cycles(&grammar),
+        vec![
+            vec![Symbol::Nonterminal('S')],
+            vec![Symbol::Nonterminal('T')]
+        ]
+    );
+}
+
+#[test]
+fn test_left_recursion_cycles_indirect() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> Aa".to_string(),
+        "A -> Sb c".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(
+        left_recursion_cycles(&grammar),
+        vec![vec![Symbol::Nonterminal('A'), Symbol::Nonterminal('S')]]
+    );
+}
+
+#[test]
+fn test_left_recursion_cycles_hidden_by_nullable_prefix() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> A".to_string(),
+        "A -> BAc a".to_string(),
+        "B -> e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(
+        left_recursion_cycles(&grammar),
+        vec![vec![Symbol::Nonterminal('A')]]
+    );
+}
+
+#[test]
+fn test_left_recursion_cycles_non_nullable_prefix() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> A".to_string(),
+        "A -> BAc a".to_string(),
+        "B -> b".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(left_recursion_cycles(&grammar).is_empty());
+}
+
+#[test]
+fn test_min_lookahead() {
+    let lines = vec!["1".to_string(), "S -> ab ac".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(min_lookahead(&grammar, 3), Some(2));
+    assert_eq!(min_lookahead(&grammar, 1), None);
+
+    let lines = vec!["1".to_string(), "S -> a b".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(min_lookahead(&grammar, 3), Some(1));
+}
+
+#[test]
+fn test_min_lookahead_left_recursive() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(min_lookahead(&grammar, 4), None);
+}
+
+#[test]
+fn test_equivalent_up_to() {
+    let grammar = |lines: &[&str]| {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        Grammar::parse(&lines).unwrap()
+    };
+    let original = grammar(&["1", "S -> ab ac"]);
+    let factored = grammar(&["2", "S -> aA", "A -> b c"]);
+    assert!(equivalent_up_to(&original, &factored, 4));
+    assert_eq!(shortest_difference(&original, &factored, 4), None);
+
+    // a^n b^n and a*b* first differ on "a"
+    let balanced = grammar(&["1", "S -> aSb e"]);
+    let regular = grammar(&["2", "S -> aS B", "B -> bB e"]);
+    assert!(!equivalent_up_to(&balanced, &regular, 3));
+    assert_eq!(
+        shortest_difference(&balanced, &regular, 3),
+        Some("a".to_string())
+    );
+    assert_eq!(shortest_difference(&balanced, &regular, 0), None);
+}
+
+#[test]
+fn test_grammar_analysis_builds_both_parsers() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+    let analysis = GrammarAnalysis::new(Grammar::parse(&lines).unwrap());
+
+    assert_eq!(analysis.classification(), Classification::Both);
+    assert_eq!(
+        analysis.nullable(),
+        &[Symbol::Nonterminal('B')].into_iter().collect::<HashSet<_>>()
+    );
+    let first_s = analysis.first_sets().first_of(Symbol::Nonterminal('S'));
+    assert!(first_s.contains(&Symbol::Terminal('d')));
+    let follow_a = analysis.follow_sets().follow_of(Symbol::Nonterminal('A'));
+    assert!(follow_a.contains(&Symbol::Terminal('b')));
+
+    let ll1 = analysis.ll1_parser().unwrap();
+    let slr1 = analysis.slr1_parser().unwrap();
+    for (input, expected) in [("d", true), ("adbc", true), ("a", false)] {
+        assert_eq!(ll1.parse(input), expected);
+        assert_eq!(slr1.parse(input), expected);
+    }
+}
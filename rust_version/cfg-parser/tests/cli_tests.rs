@@ -0,0 +1,254 @@
+//! End-to-end tests running the `cfg_parser` binary.
+
+use std::io::Write;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Runs the binary with `args`, feeding `input` on stdin, and returns stdout.
+fn run_cli(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cfg_parser"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start cfg_parser");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// A grammar file in the temporary directory, deleted when dropped.
+struct GrammarFile(PathBuf);
+
+impl Deref for GrammarFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for GrammarFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Writes `contents` to a file in the temporary directory.
+fn grammar_file(name: &str, contents: &str) -> GrammarFile {
+    let path = std::env::temp_dir().join(format!("cfg_parser_{}_{}.txt", name, std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    GrammarFile(path)
+}
+
+const EXPRESSION_GRAMMAR: &str = "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\n";
+
+#[test]
+fn test_without_trace_prints_only_verdicts() {
+    let output = run_cli(&[], "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\ni+i\n(i\n\n");
+    assert_eq!(output, "Grammar is SLR(1).\nyes\nno\n");
+}
+
+#[test]
+fn test_trace_slr1_only() {
+    let output = run_cli(
+        &["--trace"],
+        "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\ni+i\n\n",
+    );
+    assert!(output.starts_with("Grammar is SLR(1).\nyes\n"));
+    assert!(output.contains("Stack"));
+    assert!(
+        output.contains("0 i 2"),
+        "missing stack state in:\n{}",
+        output
+    );
+    assert!(output.contains("reduce F → i"));
+    assert!(output.contains("accept"));
+}
+
+#[test]
+fn test_trace_interactive_ll1() {
+    let output = run_cli(&["--trace"], "2\nS -> aSb A\nA -> c e\nT\nacb\nb\n\nQ\n");
+    assert!(output.contains("yes\n"));
+    assert!(output.contains("no\n"));
+    assert!(output.contains("$S"), "missing stack state in:\n{}", output);
+    assert!(output.contains("match a"));
+    assert!(output.contains("error"));
+}
+
+#[test]
+fn test_unknown_argument_fails() {
+    let status = Command::new(env!("CARGO_BIN_EXE_cfg_parser"))
+        .arg("--bogus")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn test_interactive_grammar_reload() {
+    let input = "\
+1
+S -> aS b
+T
+ab
+c
+
+:grammar
+1
+S -> cS d
+T
+ab
+ccd
+
+:grammar
+1
+S -> S+S i
+T
+ccd
+
+Q
+";
+    let output = run_cli(&[], input);
+    assert_eq!(output.matches("Grammar reloaded.").count(), 1);
+
+    // Before the reload: "ab" yes, "c" no; after: "ab" no, "ccd" yes. The
+    // ambiguous third grammar is rejected, so "ccd" is still accepted.
+    let verdicts: Vec<&str> = output
+        .lines()
+        .filter(|line| *line == "yes" || *line == "no")
+        .collect();
+    assert_eq!(verdicts, vec!["yes", "no", "no", "yes", "yes"]);
+}
+
+#[test]
+fn test_explain_prints_rejection_position() {
+    let output = run_cli(
+        &["--explain"],
+        "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\ni+i)\ni\n\n",
+    );
+    assert_eq!(
+        output,
+        "Grammar is SLR(1).\n\
+         no\n\
+         unexpected ')' at position 3; expected one of: '*', '+', end of input\n\
+         yes\n"
+    );
+}
+
+#[test]
+fn test_analyze_subcommand() {
+    let path = grammar_file("analyze", EXPRESSION_GRAMMAR);
+    let output = run_cli(&["analyze", path.to_str().unwrap()], "");
+    assert!(output.starts_with("Grammar:\nS → S+T | T\n"));
+    assert!(output.contains("FIRST(S) = {(, i}"));
+    assert!(output.contains("FOLLOW(S) = {), +, $}"));
+    assert!(output.contains("Grammar is SLR(1).\n"));
+    assert!(output.contains("LL(1) conflict at M[S, (]"));
+}
+
+#[test]
+fn test_parse_subcommand_reads_strings_from_file() {
+    let path = grammar_file("parse", &format!("{}i+i\n(i\n\n", EXPRESSION_GRAMMAR));
+    let output = run_cli(&["parse", path.to_str().unwrap()], "");
+    assert_eq!(output, "Grammar is SLR(1).\nyes\nno\n");
+}
+
+#[test]
+fn test_convert_subcommand() {
+    let path = grammar_file("convert", EXPRESSION_GRAMMAR);
+    let path = path.to_str().unwrap();
+
+    let output = run_cli(&["convert", path, "--to", "ll1"], "");
+    assert_eq!(
+        output,
+        "S → TA\nA → +TA | ε\nB → *FB | ε\nF → (S) | i\nT → FB\n"
+    );
+    assert!(run_cli(&["convert", path, "--to", "cnf"], "").starts_with("S → "));
+    assert!(run_cli(&["convert", path, "--to", "gnf"], "").starts_with("S → "));
+}
+
+#[test]
+fn test_export_subcommand() {
+    let path = grammar_file("export", EXPRESSION_GRAMMAR);
+    let path = path.to_str().unwrap();
+
+    let csv = run_cli(&["export", path, "--format", "csv"], "");
+    assert!(csv.starts_with("state,(,),*,+,i,$,F,S,T\n0,s1,,,,s2,,3,4,5\n"));
+    let dot = run_cli(&["export", path, "--format", "dot"], "");
+    assert!(dot.starts_with("digraph LR0 {"));
+    let latex = run_cli(&["export", path, "--format", "latex"], "");
+    assert!(latex.contains("\\begin{align*}"));
+    assert!(latex.contains("\\begin{tabular}"));
+}
+
+#[test]
+fn test_equiv_subcommand() {
+    let original = grammar_file("equiv_original", "1\nS -> ab ac\n");
+    let factored = grammar_file("equiv_factored", "2\nS -> aA\nA -> b c\n");
+    let output = run_cli(
+        &[
+            "equiv",
+            original.to_str().unwrap(),
+            factored.to_str().unwrap(),
+            "--max-len",
+            "4",
+        ],
+        "",
+    );
+    assert_eq!(output, "Equivalent up to length 4.\n");
+
+    let different = grammar_file("equiv_different", "2\nS -> aA\nA -> b c d\n");
+    let output = run_cli(
+        &[
+            "equiv",
+            original.to_str().unwrap(),
+            different.to_str().unwrap(),
+            "--max-len",
+            "4",
+        ],
+        "",
+    );
+    assert_eq!(
+        output,
+        format!(
+            "Not equivalent: \"ad\" is only accepted by {}.\n",
+            different.display()
+        )
+    );
+}
+
+#[test]
+fn test_subcommand_argument_errors() {
+    let path = grammar_file("errors", EXPRESSION_GRAMMAR);
+    let path = path.to_str().unwrap();
+    for args in [
+        vec!["convert", path],
+        vec!["convert", path, "--to", "xyz"],
+        vec!["export", path, "--to", "cnf"],
+        vec!["analyze"],
+        vec!["--format", "dot"],
+        vec!["equiv", path, "--max-len", "3"],
+        vec!["equiv", path, path],
+        vec!["analyze", path, path],
+    ] {
+        let status = Command::new(env!("CARGO_BIN_EXE_cfg_parser"))
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success(), "{:?} should fail", args);
+    }
+}
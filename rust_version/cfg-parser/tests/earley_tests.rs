@@ -0,0 +1,190 @@
+//! Unit tests for the Earley parser
+
+use cfg_parser::earley::EarleyParser;
+use cfg_parser::grammar::Grammar;
+use std::collections::HashSet;
+
+fn ambiguous_grammar() -> Grammar {
+    let lines = vec!["1".to_string(), "S -> S+S i".to_string()];
+    Grammar::parse(&lines).unwrap()
+}
+
+#[test]
+fn test_earley_recognizes_ambiguous_grammar() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+
+    assert!(parser.parse("i"));
+    assert!(parser.parse("i+i"));
+    assert!(parser.parse("i+i+i"));
+    assert!(!parser.parse(""));
+    assert!(!parser.parse("i+"));
+    assert!(!parser.parse("+i"));
+}
+
+#[test]
+fn test_earley_handles_epsilon_productions() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+    let parser = EarleyParser::new(Grammar::parse(&lines).unwrap());
+
+    assert!(parser.parse("d"));
+    assert!(parser.parse("ad"));
+    assert!(parser.parse("dbc"));
+    assert!(parser.parse("adbbcc"));
+    assert!(!parser.parse("a"));
+    assert!(!parser.parse("dbcc"));
+}
+
+#[test]
+fn test_parse_forest_rejected_input() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+    assert!(parser.parse_forest("i+").is_none());
+}
+
+#[test]
+fn test_parse_forest_unambiguous_input() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+    let forest = parser.parse_forest("i+i").unwrap();
+
+    assert_eq!(forest.start, 0);
+    assert_eq!(forest.end, 3);
+    assert_eq!(forest.trees(10).count(), 1);
+}
+
+#[test]
+fn test_parse_forest_ambiguous_input_yields_two_trees() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+    let forest = parser.parse_forest("i+i+i").unwrap();
+
+    let trees: Vec<String> = forest.trees(10).map(|t| t.to_string()).collect();
+    let distinct: HashSet<&String> = trees.iter().collect();
+
+    assert_eq!(trees.len(), 2);
+    assert_eq!(distinct.len(), 2);
+    assert!(distinct.contains(&"S(S(S(i) + S(i)) + S(i))".to_string()));
+    assert!(distinct.contains(&"S(S(i) + S(S(i) + S(i)))".to_string()));
+}
+
+#[test]
+fn test_parse_forest_tree_bound() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+    let forest = parser.parse_forest("i+i+i+i").unwrap();
+
+    // Catalan(3) = 5 trees, but the bound caps the enumeration
+    assert_eq!(forest.trees(100).count(), 5);
+    assert_eq!(forest.trees(3).count(), 3);
+}
+
+#[test]
+fn test_count_trees() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+
+    // Catalan(5) = 42 trees for six operands
+    let forest = parser.parse_forest("i+i+i+i+i+i").unwrap();
+    assert_eq!(forest.count_trees(100), 42);
+    assert_eq!(forest.count_trees(10), 10);
+    assert_eq!(forest.trees(100).count(), 42);
+
+    // Exponentially many trees, but shared nodes are only visited once
+    let long = format!("i{}", "+i".repeat(40));
+    let forest = parser.parse_forest(&long).unwrap();
+    assert_eq!(forest.count_trees(2), 2);
+    assert_eq!(forest.trees(2).count(), 2);
+    assert!(parser.is_ambiguous(&long));
+}
+
+#[test]
+fn test_parse_forest_omits_unit_cycles() {
+    // X ⇒ Y ⇒ X is a cycle, so the only tree is S(X(a))
+    let lines = vec![
+        "3".to_string(),
+        "S -> X".to_string(),
+        "X -> Y a".to_string(),
+        "Y -> X".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let parser = EarleyParser::new(grammar.clone());
+
+    let forest = parser.parse_forest("a").unwrap();
+    let trees: Vec<String> = forest.trees(10).map(|tree| tree.to_string()).collect();
+    assert_eq!(trees, vec!["S(X(a))"]);
+    assert_eq!(forest.count_trees(10), 1);
+    assert!(!parser.is_ambiguous("a"));
+    assert_eq!(grammar.num_derivations("a", 10), 1);
+}
+
+#[test]
+fn test_parse_forest_counts_every_cycle_free_tree() {
+    // S(X(a)), S(X(Y(a))), S(Y(a)) and S(Y(X(a))); a node cut below one
+    // ancestor must not be reused below the other
+    let lines = vec![
+        "3".to_string(),
+        "S -> X Y".to_string(),
+        "X -> Y a".to_string(),
+        "Y -> X a".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let parser = EarleyParser::new(grammar.clone());
+
+    let forest = parser.parse_forest("a").unwrap();
+    let trees: HashSet<String> = forest.trees(10).map(|tree| tree.to_string()).collect();
+    let expected: HashSet<String> = ["S(X(a))", "S(X(Y(a)))", "S(Y(a))", "S(Y(X(a)))"]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(trees, expected);
+    assert_eq!(forest.count_trees(10), 4);
+    assert_eq!(grammar.num_derivations("a", 10), 4);
+}
+
+#[test]
+fn test_is_ambiguous() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+
+    assert!(parser.is_ambiguous("i+i+i"));
+    assert!(!parser.is_ambiguous("i+i"));
+    assert!(!parser.is_ambiguous("i"));
+    assert!(!parser.is_ambiguous("i+"));
+}
+
+#[test]
+fn test_ambiguous_inputs_shortest_first() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+    let witnesses = parser.ambiguous_inputs(5);
+    assert_eq!(witnesses, vec!["i+i+i".to_string()]);
+
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+    let unambiguous = EarleyParser::new(Grammar::parse(&lines).unwrap());
+    assert!(unambiguous.ambiguous_inputs(4).is_empty());
+}
+
+#[test]
+fn test_earley_recognize_prefix() {
+    let parser = EarleyParser::new(ambiguous_grammar());
+
+    assert!(parser.recognize_prefix("i+"));
+    assert!(parser.recognize_prefix("i+i"));
+    assert!(parser.recognize_prefix(""));
+    assert!(!parser.recognize_prefix("+i"));
+    assert!(!parser.recognize_prefix("ii"));
+    assert!(!parser.parse("i+"));
+
+    // B derives no string, so a can only be followed by c
+    let lines = vec![
+        "2".to_string(),
+        "S -> aB ac".to_string(),
+        "B -> bB".to_string(),
+    ];
+    let parser = EarleyParser::new(Grammar::parse(&lines).unwrap());
+    assert!(parser.recognize_prefix("a"));
+    assert!(!parser.recognize_prefix("ab"));
+    assert!(parser.parse("ac"));
+}
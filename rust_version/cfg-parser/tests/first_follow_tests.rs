@@ -1,8 +1,9 @@
 //! Unit tests for FIRST and FOLLOW set computation
 
 use cfg_parser::first_follow::*;
-use cfg_parser::grammar::Grammar;
+use cfg_parser::grammar::{Grammar, GrammarConfig};
 use cfg_parser::symbol::Symbol;
+use std::collections::HashSet;
 
 #[test]
 fn test_first_sets_simple() {
@@ -109,3 +110,289 @@ fn test_complex_first_follow() {
     assert!(follow_a.contains(&Symbol::Terminal('b')));
     assert!(follow_a.contains(&Symbol::EndMarker));
 }
+
+#[test]
+fn test_nullable_symbols() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let nullable = nullable_symbols(&grammar);
+
+    assert!(nullable.contains(&Symbol::Nonterminal('B')));
+    assert!(!nullable.contains(&Symbol::Nonterminal('A')));
+    assert!(!nullable.contains(&Symbol::Nonterminal('S')));
+}
+
+#[test]
+fn test_first_and_follow_sets_k() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> Aab Ac".to_string(),
+        "A -> a e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets_k(&grammar, 2);
+    let follow_sets = compute_follow_sets_k(&grammar, &first_sets, 2);
+
+    let a = Symbol::Terminal('a');
+    let b = Symbol::Terminal('b');
+    let c = Symbol::Terminal('c');
+
+    let first_a = &first_sets[&Symbol::Nonterminal('A')];
+    assert_eq!(first_a.len(), 2);
+    assert!(first_a.contains(&vec![a]));
+    assert!(first_a.contains(&vec![]));
+
+    let first_s = &first_sets[&Symbol::Nonterminal('S')];
+    assert_eq!(first_s.len(), 4);
+    for string in [vec![a, a], vec![a, b], vec![a, c], vec![c]] {
+        assert!(first_s.contains(&string));
+    }
+
+    let follow_a = &follow_sets[&Symbol::Nonterminal('A')];
+    assert_eq!(follow_a.len(), 2);
+    assert!(follow_a.contains(&vec![a, b]));
+    assert!(follow_a.contains(&vec![c, Symbol::EndMarker]));
+}
+
+#[test]
+fn test_first_and_follow_accessor_defaults() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> AB".to_string(),
+        "A -> a".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let a = Symbol::Nonterminal('A');
+    assert!(first_sets.contains(a, Symbol::Terminal('a')));
+    assert!(!first_sets.contains(a, Symbol::Epsilon));
+    assert!(follow_sets.contains(Symbol::Nonterminal('S'), Symbol::EndMarker));
+
+    // Unknown symbols have empty sets instead of panicking
+    let missing = Symbol::Nonterminal('Z');
+    assert!(first_sets.first_of(missing).is_empty());
+    assert!(follow_sets.follow_of(missing).is_empty());
+    assert!(!follow_sets.contains(missing, Symbol::EndMarker));
+
+    // The newtypes still dereference to the underlying maps
+    assert_eq!(first_sets.first_of(a), first_sets.get(&a).unwrap());
+    assert!(FirstSets::default().is_empty());
+}
+
+#[test]
+fn test_first_follow_to_latex() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let latex = to_latex(&grammar, &first_sets, &follow_sets);
+    assert!(latex.starts_with("\\begin{tabular}{lll}\n"));
+    assert!(latex.contains("$A$ & $\\{\\varepsilon, c\\}$ & $\\{b, \\$\\}$ \\\\\n"));
+    assert!(latex.ends_with("\\end{tabular}\n"));
+}
+
+#[test]
+fn test_first_of_string_leading_terminal() {
+    let a = Symbol::Terminal('a');
+    let b = Symbol::Nonterminal('B');
+
+    // B is nullable with FIRST(B) = {b, ε}; none of it may leak into the result
+    let lines = vec![
+        "2".to_string(),
+        "S -> aB".to_string(),
+        "B -> b e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let expected: HashSet<Symbol> = [a].into_iter().collect();
+    assert_eq!(first_of_string(&first_sets, &[a, b]), expected);
+
+    // The terminal needs no entry of its own in the FIRST sets
+    assert_eq!(first_of_string(&FirstSets::default(), &[a, b]), expected);
+}
+
+#[test]
+fn test_first_of_suffix() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aAb".to_string(),
+        "A -> c e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let production = &grammar.all_productions()[0];
+
+    // A is nullable, so FIRST(Ab) = {c, b}
+    let expected: HashSet<Symbol> = [Symbol::Terminal('c'), Symbol::Terminal('b')]
+        .into_iter()
+        .collect();
+    assert_eq!(first_of_suffix(&first_sets, production, 1), expected);
+
+    // Past the end of the body the suffix is empty
+    let epsilon: HashSet<Symbol> = [Symbol::Epsilon].into_iter().collect();
+    assert_eq!(first_of_suffix(&first_sets, production, 3), epsilon);
+    assert_eq!(first_of_suffix(&first_sets, production, 7), epsilon);
+}
+
+#[test]
+fn test_single_symbol_first_and_follow_queries() {
+    let lines = vec![
+        "5".to_string(),
+        "S -> TA".to_string(),
+        "A -> +TA e".to_string(),
+        "T -> FB".to_string(),
+        "B -> *FB e".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    for nt in ['S', 'A', 'T', 'B', 'F'] {
+        let nt = Symbol::Nonterminal(nt);
+        assert_eq!(&first_of(&grammar, nt), first_sets.first_of(nt));
+        assert_eq!(&follow_of(&grammar, nt), follow_sets.follow_of(nt));
+    }
+
+    let a = Symbol::Nonterminal('A');
+    let expected_first: HashSet<Symbol> = [Symbol::Terminal('+'), Symbol::Epsilon]
+        .into_iter()
+        .collect();
+    let expected_follow: HashSet<Symbol> = [Symbol::Terminal(')'), Symbol::EndMarker]
+        .into_iter()
+        .collect();
+    assert_eq!(first_of(&grammar, a), expected_first);
+    assert_eq!(follow_of(&grammar, a), expected_follow);
+}
+
+#[test]
+fn test_follow_start_symbol_on_rhs() {
+    let b = Symbol::Terminal('b');
+    let d = Symbol::Terminal('d');
+
+    // S → aSb | c: the inner S is followed by b, the outer one by $
+    let lines = vec!["1".to_string(), "S -> aSb c".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let expected: HashSet<Symbol> = [b, Symbol::EndMarker].into_iter().collect();
+    assert_eq!(follow_sets.follow_of(Symbol::Nonterminal('S')), &expected);
+
+    // $ reaches S but not A, which is only ever followed by b
+    let lines = vec![
+        "2".to_string(),
+        "S -> aAb c".to_string(),
+        "A -> Sd".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let follow_s: HashSet<Symbol> = [d, Symbol::EndMarker].into_iter().collect();
+    let follow_a: HashSet<Symbol> = [b].into_iter().collect();
+    assert_eq!(follow_sets.follow_of(Symbol::Nonterminal('S')), &follow_s);
+    assert_eq!(follow_sets.follow_of(Symbol::Nonterminal('A')), &follow_a);
+}
+
+#[test]
+fn test_validate_sets() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    assert!(validate(&grammar, &first_sets, &follow_sets).is_ok());
+
+    let s = Symbol::Nonterminal('S');
+    let t = Symbol::Nonterminal('T');
+    let plus = Symbol::Terminal('+');
+
+    let mut bad_first = first_sets.clone();
+    bad_first.get_mut(&plus).unwrap().insert(Symbol::Terminal('i'));
+    let err = validate(&grammar, &bad_first, &follow_sets).unwrap_err();
+    assert!(err.to_string().contains("FIRST(+)"));
+
+    let mut bad_first = first_sets.clone();
+    bad_first.get_mut(&s).unwrap().insert(t);
+    assert!(validate(&grammar, &bad_first, &follow_sets).is_err());
+
+    let mut bad_follow = follow_sets.clone();
+    bad_follow.get_mut(&s).unwrap().remove(&Symbol::EndMarker);
+    let err = validate(&grammar, &first_sets, &bad_follow).unwrap_err();
+    assert!(err.to_string().contains("missing $"));
+
+    let mut bad_follow = follow_sets.clone();
+    bad_follow.get_mut(&t).unwrap().insert(Symbol::Epsilon);
+    let err = validate(&grammar, &first_sets, &bad_follow).unwrap_err();
+    assert!(err.to_string().contains("FOLLOW(T)"));
+}
+
+#[test]
+fn test_to_markdown_table() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let markdown = to_markdown_table(&grammar, &first_sets, &follow_sets);
+    assert_eq!(
+        markdown,
+        "| Nonterminal | FIRST | FOLLOW |\n\
+         |---|---|---|\n\
+         | F | {(, i} | {), *, +, $} |\n\
+         | S | {(, i} | {), +, $} |\n\
+         | T | {(, i} | {), *, +, $} |\n"
+    );
+}
+
+#[test]
+fn test_to_markdown_table_escapes_pipes() {
+    let lines = vec!["1".to_string(), "S -> |S b".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let markdown = to_markdown_table(&grammar, &first_sets, &follow_sets);
+    assert!(markdown.contains("| S | {b, \\|} | {$} |"), "{}", markdown);
+}
+
+#[test]
+fn test_set_renderings_honor_end_marker() {
+    let lines = vec!["2".to_string(), "S -> aA".to_string(), "A -> $A e".to_string()];
+    let config = GrammarConfig::default().with_end_marker('#');
+    let grammar = Grammar::parse_with_config(&lines, config).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let follow = follow_sets.follow_of(Symbol::Nonterminal('S'));
+    assert_eq!(format_set(follow, grammar.config()), "{#}");
+    let markdown = to_markdown_table(&grammar, &first_sets, &follow_sets);
+    assert!(markdown.contains("| A | {ε, $} | {#} |"), "{}", markdown);
+    assert_eq!(
+        format_follow_sets(&grammar, &follow_sets),
+        "FOLLOW(A) = {#}\nFOLLOW(S) = {#}\n"
+    );
+}
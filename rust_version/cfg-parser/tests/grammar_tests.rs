@@ -1,7 +1,14 @@
 //! Unit tests for the grammar module
 
+use cfg_parser::earley::EarleyParser;
+use cfg_parser::error::GrammarError;
+use cfg_parser::first_follow::{compute_first_sets, compute_follow_sets};
 use cfg_parser::grammar::*;
-use cfg_parser::symbol::Symbol;
+use cfg_parser::ll1::LL1Parser;
+use cfg_parser::slr1::SLR1Parser;
+use cfg_parser::symbol::{string_to_symbols, symbols_to_string, Symbol};
+use std::collections::HashSet;
+use std::io::Cursor;
 
 #[test]
 fn test_parse_simple_grammar() {
@@ -79,3 +86,1247 @@ fn test_complex_grammar() {
     assert!(grammar.terminals().contains(&Symbol::Terminal(')')));
     assert!(grammar.terminals().contains(&Symbol::Terminal('i')));
 }
+
+#[test]
+fn test_first_ll1_conflict_matches_build() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let conflict = first_ll1_conflict(&grammar, &first_sets, &follow_sets);
+    let build_error = LL1Parser::build(grammar, first_sets, follow_sets).unwrap_err();
+
+    assert!(conflict.is_some());
+    assert_eq!(conflict.unwrap().to_string(), build_error.to_string());
+}
+
+#[test]
+fn test_first_ll1_conflict_none_for_ll1_grammar() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    assert!(first_ll1_conflict(&grammar, &first_sets, &follow_sets).is_none());
+}
+
+#[test]
+fn test_productions_count_by_nonterminal() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> a b cA".to_string(),
+        "A -> d".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let counts = grammar.productions_count_by_nonterminal();
+    assert_eq!(counts.get(&Symbol::Nonterminal('S')), Some(&3));
+    assert_eq!(counts.get(&Symbol::Nonterminal('A')), Some(&1));
+    assert_eq!(counts.len(), 2);
+}
+
+#[test]
+fn test_derivative_by_terminal() {
+    let lines = vec!["1".to_string(), "S -> aS b".to_string()];
+
+    // L = a*b, so the derivative by 'a' is again a*b
+    let grammar = Grammar::parse(&lines).unwrap();
+    let derived = grammar.derivative(Symbol::Terminal('a')).unwrap();
+    assert_ne!(derived.start_symbol(), Symbol::Nonterminal('S'));
+
+    let parser = EarleyParser::new(derived);
+    assert!(parser.parse("ab"));
+    assert!(parser.parse("b"));
+    assert!(parser.parse("aaab"));
+    assert!(!parser.parse(""));
+    assert!(!parser.parse("a"));
+}
+
+#[test]
+fn test_derivative_through_nullable_prefix() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> Ab".to_string(),
+        "A -> a e".to_string(),
+    ];
+
+    // L = {ab, b}, so the derivative by 'b' is {ε}
+    let grammar = Grammar::parse(&lines).unwrap();
+    let derived = grammar.derivative(Symbol::Terminal('b')).unwrap();
+
+    let parser = EarleyParser::new(derived);
+    assert!(parser.parse(""));
+    assert!(!parser.parse("b"));
+}
+
+#[test]
+fn test_derivative_rejects_nonterminal() {
+    let lines = vec!["1".to_string(), "S -> a".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.derivative(Symbol::Nonterminal('S')).is_err());
+}
+
+#[test]
+fn test_production_is_epsilon() {
+    let s = Symbol::Nonterminal('S');
+    assert!(Production::new(s, vec![Symbol::Epsilon]).is_epsilon());
+    assert!(!Production::new(s, vec![Symbol::Terminal('a')]).is_epsilon());
+    assert!(!Production::new(s, vec![Symbol::Epsilon, Symbol::Terminal('a')]).is_epsilon());
+}
+
+#[test]
+fn test_production_is_unit() {
+    let s = Symbol::Nonterminal('S');
+    assert!(Production::new(s, vec![Symbol::Nonterminal('A')]).is_unit());
+    assert!(!Production::new(s, vec![Symbol::Terminal('a')]).is_unit());
+    assert!(!Production::new(s, vec![Symbol::Epsilon]).is_unit());
+    assert!(
+        !Production::new(s, vec![Symbol::Nonterminal('A'), Symbol::Nonterminal('B')]).is_unit()
+    );
+}
+
+#[test]
+fn test_production_display() {
+    let lines = vec!["1".to_string(), "S -> aS e".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let prods = grammar.get_productions(Symbol::Nonterminal('S'));
+    assert_eq!(prods[0].to_string(), "S → aS");
+    assert_eq!(prods[1].to_string(), "S → ε");
+}
+
+#[test]
+fn test_accepts_ll1_grammar() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb T".to_string(),
+        "T -> c".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.accepts("aacbb").unwrap());
+    assert!(!grammar.accepts("aacb").unwrap());
+}
+
+#[test]
+fn test_accepts_slr1_only_grammar() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.accepts("i+i*i").unwrap());
+    assert!(!grammar.accepts("i+").unwrap());
+}
+
+#[test]
+fn test_accepts_ambiguous_grammar() {
+    let lines = vec!["1".to_string(), "S -> S+S i".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.accepts("i+i+i").unwrap());
+    assert!(!grammar.accepts("i++i").unwrap());
+}
+
+#[test]
+fn test_accepts_batch_matches_accepts() {
+    let inputs = ["i+i*i", "(i)", "i+", "", "i++i", "((i))*i"];
+    // LL(1), SLR(1) only and ambiguous, one for each parser accepts picks
+    for lines in [
+        vec!["1".to_string(), "S -> (S)S e".to_string()],
+        vec![
+            "3".to_string(),
+            "S -> S+T T".to_string(),
+            "T -> T*F F".to_string(),
+            "F -> (S) i".to_string(),
+        ],
+        vec!["1".to_string(), "S -> S+S S*S (S) i".to_string()],
+    ] {
+        let grammar = Grammar::parse(&lines).unwrap();
+        let expected: Vec<bool> = inputs
+            .iter()
+            .map(|input| grammar.accepts(input).unwrap())
+            .collect();
+        assert_eq!(grammar.accepts_batch(&inputs).unwrap(), expected);
+    }
+    let grammar = Grammar::parse(&["1".to_string(), "S -> a".to_string()]).unwrap();
+    assert_eq!(grammar.accepts_batch(&[]).unwrap(), Vec::<bool>::new());
+}
+
+#[test]
+fn test_tokenize() {
+    let grammar = Grammar::parse(&["1".to_string(), "S -> S+S i\\s".to_string()]).unwrap();
+
+    assert_eq!(grammar.tokenize("i+i").unwrap(), string_to_symbols("i+i"));
+    assert_eq!(grammar.tokenize("").unwrap(), vec![]);
+    // Escaped terminals are matched by the character they stand for
+    assert_eq!(
+        grammar.tokenize("i+ ").unwrap(),
+        vec![Symbol::Terminal('i'), Symbol::Terminal('+'), Symbol::Terminal(' ')]
+    );
+
+    match grammar.tokenize("i*i") {
+        Err(GrammarError::UnknownTerminal { position, symbol }) => {
+            assert_eq!((position, symbol.as_str()), (1, "*"));
+        }
+        other => panic!("expected an unknown terminal, got {other:?}"),
+    }
+    assert!(matches!(
+        grammar.tokenize("iS"),
+        Err(GrammarError::NonterminalInInput { position: 1, .. })
+    ));
+}
+
+#[test]
+fn test_alternatives_sharing_prefix() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> A".to_string(),
+        "A -> abc abd c".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let pairs = grammar.alternatives_sharing_prefix(Symbol::Nonterminal('A'));
+
+    assert_eq!(pairs.len(), 1);
+    let (first, second, length) = &pairs[0];
+    assert_eq!(first.to_string(), "A → abc");
+    assert_eq!(second.to_string(), "A → abd");
+    assert_eq!(*length, 2);
+
+    assert!(grammar
+        .alternatives_sharing_prefix(Symbol::Nonterminal('S'))
+        .is_empty());
+}
+
+#[test]
+fn test_is_ll_k_candidate() {
+    let lines = vec!["1".to_string(), "S -> ab ac".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(!is_ll_k_candidate(&grammar, 1));
+    assert!(is_ll_k_candidate(&grammar, 2));
+}
+
+#[test]
+fn test_is_ll_k_candidate_through_follow() {
+    // A → ε is chosen on FOLLOW_k(A) = {ab, c$}, which clashes with A → a only for k = 1
+    let lines = vec![
+        "2".to_string(),
+        "S -> Aab Ac".to_string(),
+        "A -> a e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(!is_ll_k_candidate(&grammar, 1));
+    assert!(is_ll_k_candidate(&grammar, 2));
+}
+
+#[test]
+fn test_parse_under_count() {
+    let lines = vec!["2".to_string(), "S -> aA".to_string()];
+
+    let result = Grammar::parse(&lines);
+    assert!(matches!(
+        result,
+        Err(GrammarError::NotEnoughProductions {
+            expected: 2,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn test_parse_over_count() {
+    let lines = vec![
+        "1".to_string(),
+        "S -> aA".to_string(),
+        "A -> b".to_string(),
+    ];
+
+    let result = Grammar::parse(&lines);
+    assert!(matches!(
+        result,
+        Err(GrammarError::TooManyProductions {
+            expected: 1,
+            actual: 2
+        })
+    ));
+}
+
+#[test]
+fn test_parse_ignores_trailing_blank_lines() {
+    let lines = vec!["1".to_string(), "S -> a".to_string(), "  ".to_string(), String::new()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(grammar.all_productions().len(), 1);
+}
+
+#[test]
+fn test_empty_rhs_is_epsilon() {
+    let empty = vec!["2".to_string(), "S -> aA".to_string(), "A ->".to_string()];
+    let explicit = vec!["2".to_string(), "S -> aA".to_string(), "A -> e".to_string()];
+
+    let empty = Grammar::parse(&empty).unwrap();
+    let explicit = Grammar::parse(&explicit).unwrap();
+
+    assert_eq!(empty.all_productions(), explicit.all_productions());
+    assert!(empty.get_productions(Symbol::Nonterminal('A'))[0].is_epsilon());
+}
+
+#[test]
+fn test_sentential_forms() {
+    let lines = vec!["1".to_string(), "S -> aS b".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let forms = grammar.sentential_forms(2);
+
+    let expected: Vec<Vec<Symbol>> = ["S", "aS", "b", "aaS", "ab"]
+        .iter()
+        .map(|form| string_to_symbols(form))
+        .collect();
+    assert_eq!(forms, expected);
+    assert_eq!(grammar.sentential_forms(0), vec![string_to_symbols("S")]);
+}
+
+#[test]
+fn test_sentential_forms_with_epsilon() {
+    let lines = vec!["1".to_string(), "S -> aSb e".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let forms = grammar.sentential_forms(2);
+
+    assert!(forms.contains(&Vec::new()));
+    assert!(forms.contains(&string_to_symbols("ab")));
+    assert!(forms.contains(&string_to_symbols("aaSbb")));
+}
+
+#[test]
+fn test_custom_end_marker_allows_dollar_terminal() {
+    let lines = vec!["2".to_string(), "S -> aA".to_string(), "A -> $A b".to_string()];
+    let config = GrammarConfig::default().with_end_marker('#');
+
+    let grammar = Grammar::parse_with_config(&lines, config).unwrap();
+    assert!(grammar.terminals().contains(&Symbol::Terminal('$')));
+    assert_eq!(grammar.config().symbol_from_char('#'), Symbol::EndMarker);
+
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let ll1 = LL1Parser::build(grammar.clone(), first_sets, follow_sets.clone()).unwrap();
+    let slr1 = SLR1Parser::build(grammar.clone(), follow_sets).unwrap();
+
+    assert!(ll1.parse("a$$b"));
+    assert!(slr1.parse("a$$b"));
+    assert!(!ll1.parse("a$$"));
+    assert!(!slr1.parse("a$$"));
+    assert!(ll1.table_string().lines().next().unwrap().ends_with("| #"));
+}
+
+#[test]
+fn test_default_end_marker_is_dollar() {
+    let config = GrammarConfig::default();
+
+    assert_eq!(config.end_marker, '$');
+    assert_eq!(config.symbol_from_char('$'), Symbol::EndMarker);
+    assert_eq!(config.symbol_to_string(Symbol::EndMarker), "$");
+}
+
+#[test]
+fn test_merge_with_fragment() {
+    let expression = vec![
+        "2".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+    ];
+    let number = vec!["1".to_string(), "F -> num".to_string()];
+
+    let expression = Grammar::parse(&expression).unwrap();
+    let number = Grammar::parse(&number).unwrap();
+    let merged = expression.merge(&number, Symbol::Nonterminal('S')).unwrap();
+
+    assert_eq!(merged.all_productions().len(), 5);
+    assert_eq!(merged.get_productions(Symbol::Nonterminal('F')).len(), 1);
+    assert!(merged.accepts("num+num*num").unwrap());
+    assert!(!merged.accepts("num+").unwrap());
+}
+
+#[test]
+fn test_merge_deduplicates_identical_definitions() {
+    let first = vec!["2".to_string(), "S -> aA".to_string(), "A -> b".to_string()];
+    let second = vec!["1".to_string(), "A -> b".to_string()];
+
+    let first = Grammar::parse(&first).unwrap();
+    let second = Grammar::parse(&second).unwrap();
+    let merged = first.merge(&second, Symbol::Nonterminal('S')).unwrap();
+
+    assert_eq!(merged.all_productions(), first.all_productions());
+}
+
+#[test]
+fn test_merge_rejects_conflicting_definitions() {
+    let first = vec!["2".to_string(), "S -> aA".to_string(), "A -> b".to_string()];
+    let second = vec!["1".to_string(), "A -> c".to_string()];
+
+    let first = Grammar::parse(&first).unwrap();
+    let second = Grammar::parse(&second).unwrap();
+
+    assert!(first.merge(&second, Symbol::Nonterminal('S')).is_err());
+    assert!(first.merge(&first, Symbol::Terminal('a')).is_err());
+}
+
+#[test]
+fn test_parse_ignores_semantic_actions() {
+    let with_actions = vec![
+        "2".to_string(),
+        "S -> S+T { $$ = $1 + $3 } T { $$ = $1 }".to_string(),
+        "T -> i {{ nested }}".to_string(),
+    ];
+    let plain = vec!["2".to_string(), "S -> S+T T".to_string(), "T -> i".to_string()];
+    let config = GrammarConfig::default().with_strip_actions(true);
+
+    let with_actions = Grammar::parse_with_config(&with_actions, config).unwrap();
+    let plain = Grammar::parse(&plain).unwrap();
+
+    assert_eq!(with_actions.all_productions(), plain.all_productions());
+    assert_eq!(with_actions.all_productions()[0].to_string(), "S → S+T");
+    assert!(!with_actions.terminals().contains(&Symbol::Terminal('{')));
+}
+
+#[test]
+fn test_parse_rejects_unbalanced_actions() {
+    let unterminated = vec!["1".to_string(), "S -> a { $$ = 1".to_string()];
+    let unmatched = vec!["1".to_string(), "S -> a }".to_string()];
+    let config = GrammarConfig::default().with_strip_actions(true);
+
+    assert!(Grammar::parse_with_config(&unterminated, config.clone()).is_err());
+    assert!(Grammar::parse_with_config(&unmatched, config).is_err());
+}
+
+#[test]
+fn test_braces_are_terminals_by_default() {
+    let lines = vec!["2".to_string(), "S -> {A} a".to_string(), "A -> }{ e".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    assert_eq!(grammar.all_productions()[0].to_string(), "S → {A}");
+    assert_eq!(grammar.all_productions()[2].to_string(), "A → }{");
+    assert!(grammar.terminals().contains(&Symbol::Terminal('{')));
+    assert!(grammar.terminals().contains(&Symbol::Terminal('}')));
+
+    let text = grammar.to_input_format().unwrap();
+    let reparsed: Vec<String> = text.lines().map(str::to_string).collect();
+    assert_eq!(
+        Grammar::parse(&reparsed).unwrap().all_productions(),
+        grammar.all_productions()
+    );
+}
+
+#[test]
+fn test_parse_semicolon_multi_line_rules() {
+    let text = "S -> S + T\n   | T ;\nT -> T * F\n   | F ;\nF -> ( S ) | i ;\n";
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse_semicolon(text).unwrap();
+    let expected = Grammar::parse(&lines).unwrap();
+
+    assert_eq!(grammar.all_productions(), expected.all_productions());
+}
+
+#[test]
+fn test_parse_semicolon_epsilon_and_errors() {
+    let grammar = Grammar::parse_semicolon("S -> a A ;\nA -> b A | ;").unwrap();
+    assert!(grammar.get_productions(Symbol::Nonterminal('A'))[1].is_epsilon());
+
+    assert!(Grammar::parse_semicolon("S -> a ;\nA -> b").is_err());
+    assert!(Grammar::parse_semicolon("S a ;").is_err());
+    assert!(Grammar::parse_semicolon("  ").is_err());
+}
+
+#[test]
+fn test_grammar_to_latex() {
+    let lines = vec![
+        "2".to_string(),
+        "A -> c e".to_string(),
+        "S -> aSb A".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let latex = grammar.to_latex();
+    assert!(latex.contains("\\to"));
+    assert!(latex.contains("\\varepsilon"));
+    assert_eq!(
+        latex,
+        "\\begin{align*}\n\
+         S &\\to a S b \\mid A \\\\\n\
+         A &\\to c \\mid \\varepsilon\n\
+         \\end{align*}\n"
+    );
+}
+
+#[test]
+fn test_lint_split_declarations() {
+    let lines = vec![
+        "4".to_string(),
+        "S -> AB".to_string(),
+        "A -> a".to_string(),
+        "B -> b".to_string(),
+        "A -> c".to_string(),
+    ];
+
+    let lints = lint(&lines).unwrap();
+    assert_eq!(
+        lints,
+        vec![Lint::SplitDeclaration {
+            nonterminal: Symbol::Nonterminal('A'),
+            lines: vec![2, 4],
+        }]
+    );
+    assert_eq!(lints[0].to_string(), "alternatives of A are split across lines 2, 4");
+}
+
+#[test]
+fn test_lint_adjacent_declarations_are_clean() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> A".to_string(),
+        "A -> a".to_string(),
+        "A -> b".to_string(),
+    ];
+
+    assert!(lint(&lines).unwrap().is_empty());
+}
+
+#[test]
+fn test_is_cnf_and_is_gnf() {
+    let cnf = Grammar::parse(&[
+        "3".to_string(),
+        "S -> AB e".to_string(),
+        "A -> AB a".to_string(),
+        "B -> b".to_string(),
+    ])
+    .unwrap();
+    assert!(cnf.is_cnf());
+    assert!(!cnf.is_gnf());
+
+    let gnf = Grammar::parse(&[
+        "2".to_string(),
+        "S -> aAA b".to_string(),
+        "A -> a".to_string(),
+    ])
+    .unwrap();
+    assert!(gnf.is_gnf());
+    assert!(!gnf.is_cnf());
+
+    // S → ε is only allowed when S never appears on a right-hand side
+    let nested = Grammar::parse(&["1".to_string(), "S -> SS a e".to_string()]).unwrap();
+    assert!(!nested.is_cnf());
+
+    let expression = Grammar::parse(&[
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ])
+    .unwrap();
+    assert!(!expression.is_cnf());
+    assert!(!expression.is_gnf());
+}
+
+#[test]
+fn test_has_adjacent_nonterminals() {
+    let adjacent = Grammar::parse(&[
+        "3".to_string(),
+        "S -> aS AB".to_string(),
+        "A -> a".to_string(),
+        "B -> b".to_string(),
+    ])
+    .unwrap();
+    assert_eq!(
+        adjacent.has_adjacent_nonterminals().unwrap().to_string(),
+        "S → AB"
+    );
+
+    let operator = Grammar::parse(&["1".to_string(), "S -> S+S S*S i".to_string()]).unwrap();
+    assert_eq!(operator.has_adjacent_nonterminals(), None);
+}
+
+#[test]
+fn test_grammar_from_reader() {
+    let text = "3\nS -> S+T T\nT -> T*F F\nF -> (S) i\n\n";
+    let grammar = Grammar::from_reader(Cursor::new(text)).unwrap();
+    assert_eq!(grammar.all_productions().len(), 6);
+    assert_eq!(grammar.start_symbol(), Symbol::Nonterminal('S'));
+    assert!(grammar.accepts("i+i*i").unwrap());
+
+    let short = Grammar::from_reader(Cursor::new("3\nS -> S+T T\n"));
+    assert!(matches!(
+        short,
+        Err(GrammarError::NotEnoughProductions {
+            expected: 3,
+            actual: 1
+        })
+    ));
+
+    let long = Grammar::from_reader(Cursor::new("1\nS -> a\n\nS -> b\n"));
+    assert!(matches!(
+        long,
+        Err(GrammarError::TooManyProductions {
+            expected: 1,
+            actual: 3
+        })
+    ));
+
+    assert!(matches!(
+        Grammar::from_reader(Cursor::new("")),
+        Err(GrammarError::EmptyInput)
+    ));
+}
+
+#[test]
+fn test_whitespace_only_rhs_is_epsilon() {
+    for rule in ["A ->", "A ->   ", "A -> e"] {
+        let lines = vec!["2".to_string(), "S -> aA".to_string(), rule.to_string()];
+        let grammar = Grammar::parse(&lines).unwrap();
+        let a_prods = grammar.get_productions(Symbol::Nonterminal('A'));
+        assert_eq!(a_prods.len(), 1, "rule {:?}", rule);
+        assert!(a_prods[0].is_epsilon(), "rule {:?}", rule);
+    }
+}
+
+#[test]
+fn test_malformed_production_lines_are_rejected() {
+    for rule in ["A - > a", "-> a", "AB -> a", "a -> b"] {
+        let lines = vec!["1".to_string(), rule.to_string()];
+        assert!(
+            matches!(
+                Grammar::parse(&lines),
+                Err(GrammarError::InvalidProduction { .. })
+            ),
+            "rule {:?}",
+            rule
+        );
+    }
+}
+
+#[test]
+fn test_multi_char_lhs_is_not_truncated() {
+    let lines = vec!["2".to_string(), "S -> A".to_string(), "AB -> x".to_string()];
+    match Grammar::parse(&lines) {
+        Err(GrammarError::InvalidProduction { line, message }) => {
+            assert_eq!(line, 2);
+            assert_eq!(message, "Left-hand side must be a single nonterminal: AB");
+        }
+        other => panic!("expected InvalidProduction, got {:?}", other),
+    }
+
+    assert!(matches!(
+        Grammar::parse_semicolon("S -> A;\nAB -> x;"),
+        Err(GrammarError::InvalidProduction { line: 2, .. })
+    ));
+}
+
+#[test]
+fn test_restrict_to_terminals() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let allowed: HashSet<Symbol> = [Symbol::Terminal('i'), Symbol::Terminal('+')]
+        .into_iter()
+        .collect();
+    let restricted = grammar.restrict_to_terminals(&allowed).unwrap();
+
+    let productions: Vec<String> = restricted
+        .all_productions()
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+    assert_eq!(productions, vec!["S → S+T", "S → T", "T → F", "F → i"]);
+    assert_eq!(restricted.terminals(), &allowed);
+    assert!(restricted.accepts("i+i").unwrap());
+    assert!(!restricted.accepts("i*i").unwrap());
+
+    let nothing: HashSet<Symbol> = [Symbol::Terminal('*')].into_iter().collect();
+    assert!(grammar.restrict_to_terminals(&nothing).is_err());
+}
+
+#[test]
+fn test_terminals_in_order() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let order: Vec<Symbol> = "+*()i".chars().map(Symbol::Terminal).collect();
+    assert_eq!(grammar.terminals_in_order(), order);
+}
+
+#[test]
+fn test_to_pretty_string_groups_alternatives() {
+    let grammar = Grammar::parse_semicolon("S -> a | b ;").unwrap();
+    assert_eq!(grammar.to_pretty_string(), "S → a | b\n");
+
+    let lines = vec![
+        "2".to_string(),
+        "A -> c e".to_string(),
+        "S -> aSb A".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(grammar.to_pretty_string(), "S → aSb | A\nA → c | ε\n");
+}
+
+#[test]
+fn test_productions_indexed() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let indexed: Vec<(usize, String)> = grammar
+        .productions_indexed()
+        .map(|(index, production)| (index, production.to_string()))
+        .collect();
+    assert_eq!(
+        indexed,
+        vec![
+            (1, "S → S+T".to_string()),
+            (2, "S → T".to_string()),
+            (3, "T → T*F".to_string()),
+            (4, "T → F".to_string()),
+            (5, "F → (S)".to_string()),
+            (6, "F → i".to_string()),
+        ]
+    );
+
+    for (index, production) in grammar.productions_indexed() {
+        assert_eq!(grammar.production_index(production), Some(index));
+    }
+    // Numbers survive a reparse of the same text
+    let reparsed = Grammar::parse(&lines).unwrap();
+    assert!(reparsed.productions_indexed().eq(grammar.productions_indexed()));
+
+    let absent = Production::new(Symbol::Nonterminal('S'), vec![Symbol::Terminal('x')]);
+    assert_eq!(grammar.production_index(&absent), None);
+}
+
+#[test]
+fn test_get_productions_preserves_source_order() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> zA bB".to_string(),
+        "A -> a".to_string(),
+        "S -> c yy".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let bodies: Vec<String> = grammar
+        .get_productions(Symbol::Nonterminal('S'))
+        .iter()
+        .map(|p| symbols_to_string(&p.rhs))
+        .collect();
+    assert_eq!(bodies, vec!["zA", "bB", "c", "yy"]);
+
+    let grammar = Grammar::parse_semicolon("S -> z | b | a ; S -> c ;").unwrap();
+    let bodies: Vec<String> = grammar
+        .get_productions(Symbol::Nonterminal('S'))
+        .iter()
+        .map(|p| symbols_to_string(&p.rhs))
+        .collect();
+    assert_eq!(bodies, vec!["z", "b", "a", "c"]);
+}
+
+#[test]
+fn test_symbols_sorted() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let mut expected: Vec<Symbol> = "()*+i".chars().map(Symbol::Terminal).collect();
+    expected.extend("FST".chars().map(Symbol::Nonterminal));
+    assert_eq!(grammar.symbols(), expected);
+}
+
+#[test]
+fn test_language_is_finite() {
+    let parse = |rules: &[&str]| {
+        let mut lines = vec![rules.len().to_string()];
+        lines.extend(rules.iter().map(|r| r.to_string()));
+        Grammar::parse(&lines).unwrap()
+    };
+
+    assert!(!parse(&["S -> aS b"]).language_is_finite());
+    assert!(parse(&["S -> ab c"]).language_is_finite());
+
+    // Recursion that never grows the string does not make it infinite
+    assert!(parse(&["S -> S a"]).language_is_finite());
+    assert!(parse(&["S -> AS a", "A -> e"]).language_is_finite());
+
+    // Recursion through an unproductive or unreachable symbol does not count
+    assert!(parse(&["S -> a Bb", "B -> bB"]).language_is_finite());
+    assert!(parse(&["S -> a", "B -> bB c"]).language_is_finite());
+
+    assert!(!parse(&["S -> AB", "A -> aA e", "B -> b"]).language_is_finite());
+    assert!(parse(&["S -> e"]).language_is_finite());
+}
+
+#[test]
+fn test_clone_with_productions_keeps_start_and_config() {
+    let lines = vec!["2".to_string(), "S -> aA".to_string(), "A -> $A b".to_string()];
+    let config = GrammarConfig::default().with_end_marker('#');
+    let grammar = Grammar::parse_with_config(&lines, config.clone()).unwrap();
+
+    let a = Symbol::Nonterminal('A');
+    let productions = vec![Production::new(a, vec![Symbol::Terminal('b')])];
+    let rebuilt = grammar.clone_with_productions(productions).unwrap();
+    assert_eq!(rebuilt.start_symbol(), grammar.start_symbol());
+    assert_eq!(rebuilt.config(), &config);
+    assert_eq!(rebuilt.all_productions().len(), 1);
+    assert!(!rebuilt.terminals().contains(&Symbol::Terminal('$')));
+
+    assert!(matches!(
+        grammar.clone_with_productions(Vec::new()),
+        Err(GrammarError::EmptyInput)
+    ));
+}
+
+#[test]
+fn test_set_start_symbol() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aA".to_string(),
+        "A -> bA c".to_string(),
+    ];
+    let mut grammar = Grammar::parse(&lines).unwrap();
+    let s = Symbol::Nonterminal('S');
+    let a = Symbol::Nonterminal('A');
+
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    assert!(follow_sets.follow_of(s).contains(&Symbol::EndMarker));
+
+    grammar.set_start_symbol(a).unwrap();
+    assert_eq!(grammar.start_symbol(), a);
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    assert_eq!(follow_sets.follow_of(a), &HashSet::from([Symbol::EndMarker]));
+    assert!(!follow_sets.follow_of(s).contains(&Symbol::EndMarker));
+
+    assert!(grammar.set_start_symbol(Symbol::Nonterminal('B')).is_err());
+    assert!(grammar.set_start_symbol(Symbol::Terminal('a')).is_err());
+    assert_eq!(grammar.start_symbol(), a);
+}
+
+#[test]
+fn test_reachable_from() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let reachable = grammar.reachable_from(Symbol::Nonterminal('F'));
+    let expected: HashSet<Symbol> = "STF+*()i".chars().map(Symbol::from_char).collect();
+    assert_eq!(reachable, expected);
+
+    let lines = vec![
+        "3".to_string(),
+        "S -> aA".to_string(),
+        "A -> bB e".to_string(),
+        "B -> c".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let reachable = grammar.reachable_from(Symbol::Nonterminal('A'));
+    let expected: HashSet<Symbol> = "ABbc".chars().map(Symbol::from_char).collect();
+    assert_eq!(reachable, expected);
+    assert_eq!(
+        grammar.reachable_from(Symbol::Terminal('a')),
+        HashSet::from([Symbol::Terminal('a')])
+    );
+}
+
+#[test]
+fn test_dependency_dot() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let dot = grammar.dependency_dot();
+    assert!(dot.starts_with("digraph Dependencies {\n    \"S\" [peripheries=2];\n"));
+    assert!(dot.contains("    \"S\" -> \"T\";\n"));
+    assert!(dot.contains("    \"T\" -> \"F\";\n"));
+    assert!(dot.contains("    \"F\" -> \"S\";\n"));
+    assert!(!dot.contains("\"S\" -> \"F\""));
+    assert_eq!(dot.matches("->").count(), 5);
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn test_invalid_production_reports_line() {
+    // Production lines are numbered from 1, without the count line
+    let lines = vec!["2".to_string(), "S -> aA".to_string(), "A b".to_string()];
+
+    let error = Grammar::parse(&lines).unwrap_err();
+    assert!(matches!(
+        error,
+        GrammarError::InvalidProduction { line: 2, .. }
+    ));
+    assert_eq!(error.to_string(), "line 2: invalid production: 'A b'");
+
+    let lines = vec!["2".to_string(), "ab -> a".to_string(), "A -> b".to_string()];
+    assert!(matches!(
+        Grammar::parse(&lines),
+        Err(GrammarError::InvalidProduction { line: 1, .. })
+    ));
+}
+
+#[test]
+fn test_parse_semicolon_reports_rule_line() {
+    let config = GrammarConfig::default().with_strip_actions(true);
+    let text = "S -> aA | b ;\n\nA -> c\n  { multi\n line } | d ;\nB c ;\n";
+    assert!(matches!(
+        Grammar::parse_semicolon_with_config(text, config.clone()),
+        Err(GrammarError::InvalidProduction { line: 6, .. })
+    ));
+
+    let text = "S -> a ;\nA -> { unterminated\n";
+    assert!(matches!(
+        Grammar::parse_semicolon_with_config(text, config),
+        Err(GrammarError::InvalidProduction { line: 2, .. })
+    ));
+
+    let text = "S -> a ;\nA -> b";
+    assert!(matches!(
+        Grammar::parse_semicolon(text),
+        Err(GrammarError::InvalidProduction { line: 2, .. })
+    ));
+}
+
+#[test]
+fn test_arrow_in_right_hand_side() {
+    let lines = vec!["1".to_string(), "S -> -> a->b".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let bodies: Vec<String> = grammar
+        .get_productions(Symbol::Nonterminal('S'))
+        .iter()
+        .map(|p| symbols_to_string(&p.rhs))
+        .collect();
+    assert_eq!(bodies, vec!["->", "a->b"]);
+    assert!(grammar.terminals().contains(&Symbol::Terminal('>')));
+
+    // Such bodies round-trip through the input format
+    let text = grammar.to_input_format().unwrap();
+    assert_eq!(text, "1\nS -> -> a->b\n");
+}
+
+#[test]
+fn test_validate_reports_every_problem() {
+    let lines = vec!["2".to_string(), "S -> aB".to_string(), "A -> a$ b".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let errors = grammar.validate().unwrap_err();
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Invalid grammar format: Nonterminal B is used but has no productions",
+            "Invalid grammar format: The end marker cannot appear in A → a$",
+        ]
+    );
+
+    let lines = vec!["1".to_string(), "A -> aeb".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let errors = grammar.validate().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("Start symbol S has no productions"));
+    assert!(errors[1].to_string().contains("ε must be the whole body"));
+}
+
+#[test]
+fn test_validate_accepts_well_formed_grammar() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.validate().is_ok());
+}
+
+#[test]
+fn test_to_antlr() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> 'A e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    assert_eq!(
+        grammar.to_antlr().unwrap(),
+        "grammar Grammar;\n\
+         \n\
+         parse : s EOF ;\n\
+         \n\
+         s : 'a' s 'b' | a ;\n\
+         \n\
+         a : '\\'' a | /* ε */ ;\n"
+    );
+}
+
+#[test]
+fn test_to_antlr_escapes_and_rejects() {
+    let lines = vec!["1".to_string(), "S -> a\\n\\t\u{7f}".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(
+        grammar.to_antlr().unwrap(),
+        "grammar Grammar;\n\nparse : s EOF ;\n\ns : 'a' '\\n' '\\t' '\\u{7F}' ;\n"
+    );
+
+    let lines = vec!["1".to_string(), "S -> a\\E".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(matches!(
+        grammar.to_antlr(),
+        Err(GrammarError::InvalidFormat(_))
+    ));
+}
+
+#[test]
+fn test_to_bnf() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> \"A e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    assert_eq!(
+        grammar.to_bnf(),
+        "<S> ::= \"a\" <S> \"b\" | <A>\n<A> ::= '\"' <A> | \"\"\n"
+    );
+}
+
+#[test]
+fn test_to_ebnf_folds_repetitions() {
+    // The expression grammar after left-recursion elimination
+    let lines = vec![
+        "5".to_string(),
+        "S -> TA".to_string(),
+        "A -> +TA e".to_string(),
+        "T -> FB".to_string(),
+        "B -> *FB e".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(
+        grammar.to_ebnf(),
+        "<S> ::= <T> ( \"+\" <T> )*\n\
+         <F> ::= \"(\" <S> \")\" | \"i\"\n\
+         <T> ::= <F> ( \"*\" <F> )*\n"
+    );
+
+    let lines = vec![
+        "3".to_string(),
+        "S -> aAB".to_string(),
+        "A -> bA b".to_string(),
+        "B -> c e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(grammar.to_ebnf(), "<S> ::= \"a\" \"b\"+ \"c\"?\n");
+
+    // A repetition over another repetition is left as a rule
+    let lines = vec![
+        "3".to_string(),
+        "S -> A".to_string(),
+        "A -> BA e".to_string(),
+        "B -> b e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(grammar.to_ebnf(), "<S> ::= <A>\n<A> ::= \"b\"? <A> | \"\"\n");
+}
+
+#[test]
+fn test_parse_crlf_and_bom() {
+    // Lines split on \n keep the \r of a CRLF file
+    let lines: Vec<String> = "\u{feff}3\r\nS -> aSb A\r\nA -> c B\r\nB ->\r\n"
+        .split('\n')
+        .map(String::from)
+        .collect();
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert_eq!(grammar.to_pretty_string(), "S → aSb | A\nA → c | B\nB → ε\n");
+    assert!(lint(&lines).unwrap().is_empty());
+
+    let reader = Cursor::new("\u{feff}1\r\nS -> aS b\r\n\r\n");
+    let grammar = Grammar::from_reader(reader).unwrap();
+    assert_eq!(grammar.to_pretty_string(), "S → aS | b\n");
+
+    let grammar = Grammar::parse_semicolon("\u{feff}S -> a S | b;\r\n").unwrap();
+    assert_eq!(grammar.to_pretty_string(), "S → aS | b\n");
+}
+
+#[test]
+fn test_substitute_sub_grammar() {
+    // N is a placeholder for numbers
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) N".to_string(),
+    ];
+    let expression = Grammar::parse(&lines).unwrap();
+    // Its digit nonterminal T collides with the expression grammar's T
+    let lines = vec!["2".to_string(), "S -> TS T".to_string(), "T -> 0 1 2".to_string()];
+    let number = Grammar::parse(&lines).unwrap();
+
+    let grammar = expression.substitute(Symbol::Nonterminal('N'), &number).unwrap();
+    assert_eq!(grammar.start_symbol(), Symbol::Nonterminal('S'));
+    assert_eq!(
+        grammar.to_pretty_string(),
+        "S → S+T | T\nA → 0 | 1 | 2\nF → (S) | N\nN → AN | A\nT → T*F | F\n"
+    );
+    assert!(grammar.accepts("12+0*(21)").unwrap());
+    assert!(!grammar.accepts("1+").unwrap());
+
+    assert!(expression.substitute(Symbol::Nonterminal('X'), &number).is_err());
+}
+
+#[test]
+fn test_num_derivations() {
+    let lines = vec!["1".to_string(), "S -> S+S i".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    assert_eq!(grammar.num_derivations("i", 10), 1);
+    assert_eq!(grammar.num_derivations("i+i", 10), 1);
+    assert_eq!(grammar.num_derivations("i+i+i", 10), 2);
+    // Catalan(3) trees, capped
+    assert_eq!(grammar.num_derivations("i+i+i+i", 10), 5);
+    assert_eq!(grammar.num_derivations("i+i+i+i", 3), 3);
+    assert_eq!(grammar.num_derivations("i+", 10), 0);
+
+    // Catalan(15) trees, counted without being built
+    let long = vec!["i"; 16].join("+");
+    assert_eq!(grammar.num_derivations(&long, usize::MAX), 9_694_845);
+}
+
+#[test]
+fn test_parse_escaped_terminals() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> a\\sS A".to_string(),
+        "A -> \\t \\n \\\\".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let terminals: HashSet<Symbol> = ['a', ' ', '\t', '\n', '\\']
+        .into_iter()
+        .map(Symbol::Terminal)
+        .collect();
+    assert_eq!(grammar.terminals(), &terminals);
+    assert!(grammar.accepts("a a \t").unwrap());
+    assert!(grammar.accepts("a \n").unwrap());
+    assert!(grammar.accepts("\\").unwrap());
+
+    // Writing the grammar re-escapes the terminals, so it reads back the same
+    let text = grammar.to_input_format().unwrap();
+    assert_eq!(text, "2\nS -> a\\sS A\nA -> \\t \\n \\\\\n");
+    let lines: Vec<String> = text.lines().map(String::from).collect();
+    assert_eq!(Grammar::parse(&lines).unwrap().terminals(), &terminals);
+
+    let grammar = Grammar::parse_semicolon("S -> a \\s S | \\t;").unwrap();
+    assert!(grammar.accepts("a \t").unwrap());
+}
+
+#[test]
+fn test_rename_to_canonical() {
+    let lines = vec![
+        "4".to_string(),
+        "S -> BaC".to_string(),
+        "B -> b".to_string(),
+        "C -> Bc e".to_string(),
+        "D -> d".to_string(),
+    ];
+    let first = Grammar::parse(&lines).unwrap();
+
+    // The same grammar with every nonterminal named differently
+    let production =
+        |lhs: char, rhs: &str| Production::new(Symbol::Nonterminal(lhs), string_to_symbols(rhs));
+    let second = Grammar::from_productions_with_start(
+        vec![
+            production('P', "QaR"),
+            production('Q', "b"),
+            production('R', "Qc"),
+            production('R', "e"),
+            production('Z', "d"),
+        ],
+        Symbol::Nonterminal('P'),
+    )
+    .unwrap();
+
+    let first = first.rename_to_canonical().unwrap();
+    let second = second.rename_to_canonical().unwrap();
+    assert_eq!(first.start_symbol(), Symbol::Nonterminal('S'));
+    assert_eq!(first.all_productions(), second.all_productions());
+    assert_eq!(first.to_pretty_string(), "S → AaB\nA → b\nB → Ac | ε\nC → d\n");
+}
+
+#[test]
+fn test_alternatives_iter() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let rendered: Vec<String> = grammar
+        .alternatives_iter()
+        .map(|(lhs, rhs)| format!("{} -> {}", lhs, symbols_to_string(rhs)))
+        .collect();
+    assert_eq!(rendered, ["S -> aSb", "S -> A", "A -> c", "A -> ε"]);
+
+    let rebuilt: Vec<Production> = grammar
+        .alternatives_iter()
+        .map(|(lhs, rhs)| Production::new(lhs, rhs.to_vec()))
+        .collect();
+    assert_eq!(rebuilt, grammar.all_productions());
+}
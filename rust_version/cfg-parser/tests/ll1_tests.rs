@@ -1,8 +1,11 @@
 //! Unit tests for LL(1) parser
 
+use cfg_parser::error::GrammarError;
 use cfg_parser::first_follow::{compute_first_sets, compute_follow_sets};
 use cfg_parser::grammar::Grammar;
 use cfg_parser::ll1::LL1Parser;
+use cfg_parser::symbol::Symbol;
+use cfg_parser::trace::Rejection;
 
 #[test]
 fn test_ll1_simple() {
@@ -48,6 +51,26 @@ fn test_ll1_accepts_valid_strings() {
     assert!(parser.parse("adbc"));
 }
 
+#[test]
+fn test_ll1_parse_batch() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    let inputs = ["d", "adbc", "a", "", "dbbcc", "dbcc"];
+    let expected: Vec<bool> = inputs.iter().map(|input| parser.parse(input)).collect();
+    assert_eq!(expected, [true, true, false, false, true, false]);
+    assert_eq!(parser.parse_batch(&inputs), expected);
+}
+
 #[test]
 fn test_ll1_rejects_invalid_strings() {
     let lines = vec![
@@ -103,3 +126,280 @@ fn test_ll1_epsilon_production() {
     assert!(parser.parse("a"));
     assert!(parser.parse(""));
 }
+
+#[test]
+fn test_ll1_build_with_report_collects_all_conflicts() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> aA aB ab".to_string(),
+        "A -> c".to_string(),
+        "B -> d".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let result = LL1Parser::build_with_report(grammar, first_sets, follow_sets);
+    let Err(GrammarError::ConflictReport { conflicts }) = &result else {
+        panic!("expected a conflict report, got {:?}", result.err());
+    };
+    assert_eq!(conflicts.len(), 2);
+    assert!(
+        conflicts
+            .iter()
+            .all(|c| matches!(c, GrammarError::LL1Conflict { .. }))
+    );
+
+    let message = result.err().unwrap().to_string();
+    assert!(message.starts_with("2 conflict(s) found:"));
+    assert!(message.contains("\n1. LL(1) conflict at M[S, a]"));
+    assert!(message.contains("\n2. LL(1) conflict at M[S, a]"));
+}
+
+#[test]
+fn test_ll1_build_with_report_succeeds_for_ll1_grammar() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aA".to_string(),
+        "A -> b e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    assert!(LL1Parser::conflicts(&grammar, &first_sets, &follow_sets).is_empty());
+    let parser = LL1Parser::build_with_report(grammar, first_sets, follow_sets).unwrap();
+    assert!(parser.parse("ab"));
+}
+
+#[test]
+fn test_ll1_build_auto() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+
+    let (parser, transformed) = LL1Parser::build_auto(grammar.clone()).unwrap();
+    assert_ne!(transformed.all_productions(), grammar.all_productions());
+    assert_eq!(
+        parser.grammar().all_productions(),
+        transformed.all_productions()
+    );
+    assert!(parser.parse("i+i*i"));
+    assert!(parser.parse("(i+i)*i"));
+    assert!(!parser.parse("i+*i"));
+
+    // An LL(1) grammar is used as given
+    let ll1 = Grammar::parse(&["2".to_string(), "S -> aA".to_string(), "A -> b e".to_string()])
+        .unwrap();
+    let (_, unchanged) = LL1Parser::build_auto(ll1.clone()).unwrap();
+    assert_eq!(unchanged.all_productions(), ll1.all_productions());
+
+    // An ambiguous grammar stays in conflict after the transformations
+    let ambiguous = Grammar::parse(&["1".to_string(), "S -> S+S i".to_string()]).unwrap();
+    assert!(matches!(
+        LL1Parser::build_auto(ambiguous),
+        Err(GrammarError::LL1Conflict { .. })
+    ));
+}
+
+#[test]
+fn test_ll1_parse_trace() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    let trace = parser.parse_trace("ab");
+    assert!(trace.accepted);
+    let rows: Vec<(&str, &str, &str)> = trace
+        .steps
+        .iter()
+        .map(|s| (s.stack.as_str(), s.input.as_str(), s.action.as_str()))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("$S", "ab$", "S → aSb"),
+            ("$bSa", "ab$", "match a"),
+            ("$bS", "b$", "S → A"),
+            ("$bA", "b$", "A → ε"),
+            ("$b", "b$", "match b"),
+            ("$", "$", "accept"),
+        ]
+    );
+
+    for input in ["", "c", "acb", "aacbb", "b", "acbb"] {
+        let trace = parser.parse_trace(input);
+        assert_eq!(trace.accepted, parser.parse(input), "input {:?}", input);
+    }
+}
+
+#[test]
+fn test_ll1_start_symbol_on_rhs() {
+    let lines = vec!["1".to_string(), "S -> aSb c".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    // S is not nullable, so $ ∈ FOLLOW(S) must not create table entries
+    let s = Symbol::Nonterminal('S');
+    assert!(parser.table().get(&(s, Symbol::EndMarker)).is_none());
+    assert!(parser.table().get(&(s, Symbol::Terminal('b'))).is_none());
+    assert_eq!(parser.table().len(), 2);
+
+    for (input, expected) in [
+        ("c", true),
+        ("acb", true),
+        ("aacbb", true),
+        ("", false),
+        ("acbb", false),
+        ("ab", false),
+    ] {
+        assert_eq!(parser.parse(input), expected, "input {:?}", input);
+    }
+}
+
+/// Unwraps the rejection of a failed detailed parse.
+fn rejection_of(result: cfg_parser::error::Result<()>) -> Rejection {
+    match result {
+        Err(GrammarError::Rejected(rejection)) => rejection,
+        other => panic!("expected a rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ll1_parse_detailed() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    assert!(parser.parse_detailed("aacbb").is_ok());
+
+    let rejection = rejection_of(parser.parse_detailed("acbb"));
+    assert_eq!(rejection.position, 3);
+    assert_eq!(rejection.found, Symbol::Terminal('b'));
+    assert_eq!(rejection.expected, vec![Symbol::EndMarker]);
+    assert_eq!(
+        rejection.to_string(),
+        "unexpected 'b' at position 3; expected one of: end of input"
+    );
+
+    let rejection = rejection_of(parser.parse_detailed("aac"));
+    assert_eq!(rejection.position, 3);
+    assert_eq!(rejection.found, Symbol::EndMarker);
+    assert_eq!(rejection.expected, vec![Symbol::Terminal('b')]);
+}
+
+#[test]
+fn test_ll1_parse_detailed_rejects_nonterminal_input() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    match parser.parse_detailed("Sa") {
+        Err(GrammarError::NonterminalInInput { position, symbol }) => {
+            assert_eq!(position, 0);
+            assert_eq!(symbol, "S");
+        }
+        other => panic!("expected NonterminalInInput, got {:?}", other),
+    }
+    assert!(matches!(
+        parser.parse_detailed("aeb"),
+        Err(GrammarError::NonterminalInInput { position: 1, .. })
+    ));
+}
+
+#[test]
+fn test_ll1_parse_trimmed() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    assert!(!parser.parse("acb "));
+    assert!(!parser.parse(" acb"));
+    assert!(parser.parse_trimmed("acb "));
+    assert!(parser.parse_trimmed("\t acb\n"));
+    assert!(parser.parse_trimmed("  "));
+    assert!(!parser.parse_trimmed("a cb"));
+}
+
+#[test]
+fn test_ll1_to_markdown_table() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> aSb A".to_string(),
+        "A -> c e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    let markdown = parser.to_markdown_table();
+    let rows: Vec<&str> = markdown.lines().collect();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0], "|  | a | b | c | $ |");
+    assert_eq!(rows[1], "|---|---|---|---|---|");
+    assert_eq!(rows[3], "| S | S → aSb | S → A | S → A | S → A |");
+    assert!(rows.iter().all(|row| row.matches('|').count() == 6));
+}
+
+#[test]
+fn test_ll1_parse_with_limit() {
+    // Every a nests one more S, leaving a pending b on the stack
+    let lines = vec!["1".to_string(), "S -> aSb e".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = LL1Parser::build(grammar, first_sets, follow_sets).unwrap();
+
+    assert!(parser.parse_with_limit("aabb", 10).unwrap());
+    assert!(!parser.parse_with_limit("aab", 10).unwrap());
+
+    let deep = format!("{}{}", "a".repeat(1000), "b".repeat(1000));
+    assert!(parser.parse(&deep));
+    match parser.parse_with_limit(&deep, 100) {
+        Err(GrammarError::StackLimitExceeded { limit, position }) => {
+            assert_eq!(limit, 100);
+            assert_eq!(position, 97);
+        }
+        other => panic!("expected StackLimitExceeded, got {:?}", other),
+    }
+}
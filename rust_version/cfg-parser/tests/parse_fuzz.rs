@@ -0,0 +1,97 @@
+//! Robustness fuzzing of the grammar readers
+//!
+//! Random byte strings, biased towards the characters that matter to the
+//! grammar formats, are fed to every reader, which must return `Ok` or `Err`
+//! and never panic. The generator uses a fixed seed so runs are reproducible;
+//! enable the `fuzz` feature for a longer run.
+
+mod common;
+
+use cfg_parser::grammar::{lint, Grammar};
+use common::Rng;
+use std::io::Cursor;
+
+const SEED: u64 = 0x0bad_1e55;
+const CASES: usize = if cfg!(feature = "fuzz") {
+    200_000
+} else {
+    3_000
+};
+
+/// Fragments the inputs are assembled from, besides random bytes.
+const FRAGMENTS: [&str; 24] = [
+    "0",
+    "1",
+    "2",
+    "3",
+    "S",
+    "A",
+    "b",
+    "e",
+    "$",
+    " ",
+    "\n",
+    "\r\n",
+    "->",
+    "-",
+    ">",
+    "{",
+    "}",
+    ";",
+    "|",
+    "\\",
+    "\\E",
+    "\u{feff}",
+    "数",
+    "18446744073709551616",
+];
+
+/// Generates up to 40 fragments or random bytes, read as lossy UTF-8.
+fn random_input(rng: &mut Rng) -> String {
+    let mut bytes = Vec::new();
+    for _ in 0..rng.below(40) {
+        if rng.below(4) == 0 {
+            bytes.push(rng.next() as u8);
+        } else {
+            bytes.extend_from_slice(FRAGMENTS[rng.below(FRAGMENTS.len())].as_bytes());
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Generates a count line and productions with mangled right-hand sides, so
+/// the readers get past the header and into the production parsing.
+fn random_grammar_text(rng: &mut Rng) -> String {
+    let count = rng.below(5);
+    let mut text = format!("{count}\n");
+    for _ in 0..rng.below(count + 2) {
+        let lhs = FRAGMENTS[rng.below(FRAGMENTS.len())];
+        text.push_str(&format!(
+            "{lhs} -> {}\n",
+            random_input(rng).replace('\n', " ")
+        ));
+    }
+    text.push_str(&random_input(rng));
+    text
+}
+
+#[test]
+fn test_readers_never_panic() {
+    let mut rng = Rng(SEED);
+    let mut parsed = 0;
+    for _ in 0..CASES {
+        let text = if rng.below(2) == 0 {
+            random_input(&mut rng)
+        } else {
+            random_grammar_text(&mut rng)
+        };
+        let lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+
+        // Beyond the absence of a panic, only check some inputs get through
+        parsed += usize::from(Grammar::parse(&lines).is_ok());
+        let _ = lint(&lines);
+        let _ = Grammar::from_reader(Cursor::new(text.as_bytes()));
+        let _ = Grammar::parse_semicolon(&text);
+    }
+    assert!(parsed > 0, "no generated input was a valid grammar");
+}
@@ -0,0 +1,96 @@
+//! Round-trip fuzzing between `Grammar::to_input_format` and `Grammar::parse`
+//!
+//! Random small grammars are written out and read back, and both sides are
+//! compared up to production order. The generator uses a fixed seed so runs
+//! are reproducible; enable the `fuzz` feature for a longer run.
+
+mod common;
+
+use cfg_parser::grammar::{Grammar, Production};
+use cfg_parser::symbol::Symbol;
+use common::Rng;
+
+const SEED: u64 = 0x5eed_cf65;
+const CASES: usize = if cfg!(feature = "fuzz") { 20_000 } else { 300 };
+
+const NONTERMINALS: [char; 4] = ['S', 'A', 'B', 'C'];
+const TERMINALS: [char; 8] = ['a', 'b', 'c', '+', '(', ')', '-', '>'];
+
+/// Generates a grammar of one to four nonterminals with bodies of up to four
+/// symbols; an empty body is ε.
+fn random_grammar(rng: &mut Rng) -> Grammar {
+    let nonterminals = &NONTERMINALS[..1 + rng.below(NONTERMINALS.len())];
+    let mut productions = Vec::new();
+    for &nt in nonterminals {
+        for _ in 0..1 + rng.below(3) {
+            let rhs: Vec<Symbol> = (0..rng.below(5))
+                .map(|_| {
+                    if rng.below(3) == 0 {
+                        Symbol::Nonterminal(nonterminals[rng.below(nonterminals.len())])
+                    } else {
+                        Symbol::Terminal(TERMINALS[rng.below(TERMINALS.len())])
+                    }
+                })
+                .collect();
+            let rhs = if rhs.is_empty() {
+                vec![Symbol::Epsilon]
+            } else {
+                rhs
+            };
+            productions.push(Production::new(Symbol::Nonterminal(nt), rhs));
+        }
+    }
+    Grammar::from_productions_with_start(productions, Symbol::Nonterminal('S')).unwrap()
+}
+
+/// Productions sorted by left- then right-hand side.
+fn canonical(grammar: &Grammar) -> Vec<Production> {
+    let mut productions = grammar.all_productions().to_vec();
+    productions.sort_by(|a, b| (a.lhs, &a.rhs).cmp(&(b.lhs, &b.rhs)));
+    productions
+}
+
+#[test]
+fn test_input_format_round_trip() {
+    let mut rng = Rng(SEED);
+    for case in 0..CASES {
+        let grammar = random_grammar(&mut rng);
+        let text = grammar
+            .to_input_format()
+            .unwrap_or_else(|e| panic!("case {}: cannot write {:?}: {}", case, grammar, e));
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let reparsed = Grammar::parse(&lines)
+            .unwrap_or_else(|e| panic!("case {}: {} failed to parse: {}", case, text, e));
+
+        assert_eq!(
+            canonical(&reparsed),
+            canonical(&grammar),
+            "case {}:\n{}",
+            case,
+            text
+        );
+        assert_eq!(reparsed.start_symbol(), grammar.start_symbol());
+        assert_eq!(reparsed.terminals(), grammar.terminals());
+    }
+}
+
+#[test]
+fn test_input_format_rejects_unwritable_grammars() {
+    let s = Symbol::Nonterminal('S');
+    let cases = [
+        vec![Production::new(s, vec![Symbol::Terminal('e')])],
+        vec![Production::new(s, vec![Symbol::Terminal('A')])],
+    ];
+    for productions in cases {
+        let grammar = Grammar::from_productions_with_start(productions, s).unwrap();
+        assert!(grammar.to_input_format().is_err());
+    }
+
+    let a = Symbol::Nonterminal('A');
+    let grammar = Grammar::from_productions_with_start(
+        vec![Production::new(a, vec![Symbol::Terminal('a')])],
+        a,
+    )
+    .unwrap();
+    assert!(grammar.to_input_format().is_err());
+}
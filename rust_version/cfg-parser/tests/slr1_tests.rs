@@ -1,8 +1,12 @@
 //! Unit tests for SLR(1) parser
 
+use cfg_parser::error::GrammarError;
 use cfg_parser::first_follow::{compute_first_sets, compute_follow_sets};
-use cfg_parser::grammar::Grammar;
-use cfg_parser::slr1::SLR1Parser;
+use cfg_parser::grammar::{ColumnOrder, Grammar, GrammarChange, GrammarConfig, Production};
+use cfg_parser::slr1::{Action, SLR1Parser};
+use cfg_parser::symbol::Symbol;
+use cfg_parser::trace::Rejection;
+use std::collections::HashSet;
 
 #[test]
 fn test_slr1_simple() {
@@ -26,6 +30,26 @@ fn test_slr1_simple() {
     assert!(!parser.parse("(i+i)*i)"));
 }
 
+#[test]
+fn test_slr1_parse_batch() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let inputs = ["i+i", "(i)", "(i+i)*i)", "", "i*(i+i)"];
+    let expected: Vec<bool> = inputs.iter().map(|input| parser.parse(input)).collect();
+    assert_eq!(expected, [true, true, false, false, true]);
+    assert_eq!(parser.parse_batch(&inputs), expected);
+}
+
 #[test]
 fn test_slr1_accepts_valid_expressions() {
     let lines = vec![
@@ -96,6 +120,31 @@ fn test_slr1_conflict_detection() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_slr1_state_numbering_is_deterministic() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let build = || SLR1Parser::build(grammar.clone(), follow_sets.clone()).unwrap();
+
+    // Every build hashes with fresh keys, yet numbers the states the same
+    let parser = build();
+    for _ in 0..20 {
+        let other = build();
+        assert_eq!(other.to_dot(), parser.to_dot());
+        assert_eq!(other.to_csv(), parser.to_csv());
+    }
+    // Successors of a state are numbered in symbol order, so '(' comes first
+    assert!(parser.to_dot().contains("    0 -> 1 [label=\"(\"];\n"));
+}
+
 #[test]
 fn test_slr1_operator_precedence() {
     let lines = vec![
@@ -114,3 +163,937 @@ fn test_slr1_operator_precedence() {
     assert!(parser.parse("i+i*i")); // * has higher precedence
     assert!(parser.parse("(i+i)*i")); // Parentheses work
 }
+
+#[test]
+fn test_slr1_accepts_prefix() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert!(parser.accepts_prefix(""));
+    assert!(parser.accepts_prefix("i+"));
+    assert!(parser.accepts_prefix("(i*(i"));
+    assert!(parser.accepts_prefix("i+i"));
+    assert!(!parser.accepts_prefix("+"));
+    assert!(!parser.accepts_prefix("i)"));
+    assert!(!parser.parse("i+"));
+}
+
+#[test]
+fn test_slr1_valid_next() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let after_i = parser.valid_next("i");
+    assert!(after_i.contains(&Symbol::Terminal('+')));
+    assert!(after_i.contains(&Symbol::Terminal('*')));
+    assert!(after_i.contains(&Symbol::EndMarker));
+    assert!(!after_i.contains(&Symbol::Terminal('i')));
+
+    let at_start: HashSet<Symbol> = [Symbol::Terminal('('), Symbol::Terminal('i')].into();
+    assert_eq!(parser.valid_next(""), at_start);
+    assert!(parser.valid_next("+").is_empty());
+}
+
+#[test]
+fn test_slr1_epsilon_reductions() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert!(parser.parse("d"));
+    assert!(parser.parse("ad"));
+    assert!(parser.parse("dbc"));
+    assert!(parser.parse("adbbcc"));
+    assert!(!parser.parse("a"));
+    assert!(!parser.parse("dbcc"));
+}
+
+#[test]
+fn test_action_display() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let third = &grammar.all_productions()[2];
+    let reduce = Action::Reduce(grammar.production_index(third).unwrap());
+
+    assert_eq!(Action::Shift(5).to_string(), "s5");
+    assert_eq!(reduce.to_string(), "r3");
+    assert_eq!(Action::Accept.to_string(), "acc");
+    assert_eq!(reduce.production(&grammar), Some(third));
+    assert_eq!(Action::Shift(5).production(&grammar), None);
+}
+
+#[test]
+fn test_action_predicates() {
+    let reduce = Action::Reduce(1);
+
+    assert!(Action::Shift(1).is_shift() && !Action::Shift(1).is_reduce());
+    assert!(reduce.is_reduce() && !reduce.is_accept());
+    assert!(Action::Accept.is_accept() && !Action::Accept.is_shift());
+}
+
+#[test]
+fn test_slr1_action_table_accessor() {
+    let lines = vec!["1".to_string(), "S -> a".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let shift = &parser.action_table()[&(0, Symbol::Terminal('a'))];
+    assert!(shift.is_shift());
+    assert_eq!(parser.action_table().values().filter(|a| a.is_accept()).count(), 1);
+    assert_eq!(parser.goto_table()[&(0, Symbol::Nonterminal('S'))], 2);
+}
+
+#[test]
+fn test_slr1_coverage() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar.clone(), follow_sets).unwrap();
+
+    // The rejected input would reduce by T -> T*F before failing
+    let covered = parser.coverage(&["i+i", "i", "i*i)"]);
+    let mut rendered: Vec<String> = covered.iter().map(Production::to_string).collect();
+    rendered.sort();
+    assert_eq!(rendered, ["F → i", "S → S+T", "S → T", "T → F"]);
+
+    let all = parser.coverage(&["(i)*i+i"]);
+    assert_eq!(all.len(), grammar.all_productions().len());
+    assert!(parser.coverage(&[]).is_empty());
+}
+
+#[test]
+fn test_slr1_end_marker_only_accepts_or_reduces() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let on_end: Vec<&Action> = parser
+        .action_table()
+        .iter()
+        .filter(|((_, symbol), _)| symbol.is_end_marker())
+        .map(|(_, action)| action)
+        .collect();
+    assert!(on_end.iter().all(|action| !action.is_shift()));
+    assert_eq!(on_end.iter().filter(|action| action.is_accept()).count(), 1);
+    assert!(parser.parse("i+i*i"));
+}
+
+#[test]
+fn test_slr1_longest_accepted_prefix() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert_eq!(parser.longest_accepted_prefix("i+i)"), 3);
+    assert_eq!(parser.longest_accepted_prefix("+i"), 0);
+    assert_eq!(parser.longest_accepted_prefix("i+"), 2);
+    assert_eq!(parser.longest_accepted_prefix("(i*i)"), 5);
+}
+
+#[test]
+fn test_slr1_to_dot_with_conflicts() {
+    // The classic L-value grammar is LR(1) but not SLR(1)
+    let lines = vec![
+        "3".to_string(),
+        "S -> L=R R".to_string(),
+        "L -> *R i".to_string(),
+        "R -> L".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let conflicts = SLR1Parser::conflicts(&grammar, &follow_sets);
+    assert_eq!(conflicts.len(), 1);
+    let GrammarError::SLR1ShiftReduceConflict { state, symbol, .. } = &conflicts[0] else {
+        panic!("expected a shift/reduce conflict, got {:?}", conflicts[0]);
+    };
+    assert_eq!(symbol, "=");
+
+    let dot = SLR1Parser::to_dot_with_conflicts(&grammar, &follow_sets);
+    let marked: Vec<&str> = dot.lines().filter(|line| line.contains("color=red")).collect();
+    assert_eq!(marked.len(), 1);
+    assert!(marked[0].trim_start().starts_with(&format!("{} [", state)));
+    assert!(marked[0].contains("conflict on ="));
+}
+
+#[test]
+fn test_slr1_conflicts_empty_for_slr1_grammar() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    assert!(SLR1Parser::conflicts(&grammar, &follow_sets).is_empty());
+    let parser = SLR1Parser::build(grammar.clone(), follow_sets.clone()).unwrap();
+    assert_eq!(
+        SLR1Parser::to_dot_with_conflicts(&grammar, &follow_sets),
+        parser.to_dot()
+    );
+}
+
+#[test]
+fn test_slr1_items_sharing_a_shift_are_not_a_conflict() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> aA aB".to_string(),
+        "A -> b".to_string(),
+        "B -> c".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+    assert!(parser.parse("ab"));
+    assert!(parser.parse("ac"));
+}
+
+#[test]
+fn test_slr1_build_with_report() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> A".to_string(),
+        "A -> A b".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let result = SLR1Parser::build_with_report(grammar, follow_sets);
+    let Err(GrammarError::ConflictReport { conflicts }) = result else {
+        panic!("expected a conflict report");
+    };
+    assert_eq!(conflicts.len(), 1);
+    assert!(
+        conflicts
+            .iter()
+            .any(|c| matches!(c, GrammarError::SLR1ReduceReduceConflict { .. }))
+    );
+}
+
+#[test]
+fn test_slr1_compact_merges_duplicate_rows() {
+    // A and B derive no terminal string, so the states reached through them
+    // only differ in which (equally dead) state they lead to
+    let lines = vec![
+        "3".to_string(),
+        "S -> ba AB".to_string(),
+        "A -> BBB".to_string(),
+        "B -> BB".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar.clone(), follow_sets.clone()).unwrap();
+    let compact = SLR1Parser::build(grammar, follow_sets).unwrap().compact();
+
+    assert_eq!(parser.num_states(), 10);
+    assert_eq!(parser.minimal_state_count(), 8);
+    assert_eq!(compact.num_states(), 8);
+    for input in ["ba", "", "b", "a", "bab", "ab"] {
+        assert_eq!(parser.parse(input), compact.parse(input), "input {:?}", input);
+    }
+}
+
+#[test]
+fn test_slr1_compact_keeps_distinct_rows() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let compact = SLR1Parser::build(grammar, follow_sets).unwrap().compact();
+
+    assert_eq!(compact.num_states(), 12);
+    for input in ["i+i*i", "(i+i)*i", "i+", "(i))"] {
+        let expected = !input.ends_with('+') && input != "(i))";
+        assert_eq!(compact.parse(input), expected, "input {:?}", input);
+    }
+}
+
+#[test]
+fn test_slr1_parse_trace() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let trace = parser.parse_trace("i");
+    assert!(trace.accepted);
+    let actions: Vec<&str> = trace.steps.iter().map(|s| s.action.as_str()).collect();
+    assert_eq!(
+        actions,
+        vec!["shift 2", "reduce F → i", "reduce T → F", "reduce S → T", "accept"]
+    );
+    assert_eq!(trace.steps[1].stack, "0 i 2");
+    assert_eq!(trace.steps[1].input, "$");
+
+    for input in ["i+i*i", "(i+i)*i", "i+", "(i))", ""] {
+        let trace = parser.parse_trace(input);
+        assert_eq!(trace.accepted, parser.parse(input), "input {:?}", input);
+        let last = &trace.steps.last().unwrap().action;
+        assert_eq!(last == "accept", trace.accepted);
+    }
+}
+
+#[test]
+fn test_slr1_parse_with_stats() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let (accepted, stats) = parser.parse_with_stats("i+i*i");
+    assert!(accepted);
+    assert_eq!(stats.shifts, 5);
+    // i→F, F→T, T→S; i→F, F→T; i→F; T→T*F; S→S+T
+    assert_eq!(stats.reduces, 8);
+    // 0 S + T * i
+    assert_eq!(stats.max_stack_depth, 6);
+
+    let (accepted, stats) = parser.parse_with_stats("i+");
+    assert!(!accepted);
+    assert_eq!(stats.shifts, 2);
+}
+
+#[test]
+fn test_slr1_csv_first_appearance_columns() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let csv = parser.to_csv_with_order(ColumnOrder::FirstAppearance);
+    assert_eq!(csv.lines().next().unwrap(), "state,+,*,(,),i,$,F,S,T");
+    assert_eq!(
+        parser.to_csv_with_order(ColumnOrder::Symbol),
+        parser.to_csv()
+    );
+}
+
+#[test]
+fn test_slr1_rebuild_incremental() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let u = Symbol::Nonterminal('U');
+    let f = Symbol::Nonterminal('F');
+
+    // An unreachable nonterminal that changes no FOLLOW set: tables reused
+    let unrelated = GrammarChange::Add(Production::new(u, vec![Symbol::Terminal('u')]));
+    assert!(!parser.is_affected_by(&unrelated).unwrap());
+    let reused = parser.rebuild_incremental(&unrelated).unwrap();
+    assert_eq!(reused.action_table(), parser.action_table());
+    assert_eq!(reused.goto_table(), parser.goto_table());
+    assert_eq!(reused.grammar().all_productions().len(), 7);
+
+    // Unreachable, but it adds x to FOLLOW(S)
+    let follow_change = GrammarChange::Add(Production::new(
+        u,
+        vec![Symbol::Nonterminal('S'), Symbol::Terminal('x')],
+    ));
+    assert!(parser.is_affected_by(&follow_change).unwrap());
+
+    // A new alternative of a reachable nonterminal: rebuilt
+    let related = GrammarChange::Replace {
+        old: Production::new(f, vec![Symbol::Terminal('i')]),
+        new: Production::new(f, vec![Symbol::Terminal('n')]),
+    };
+    assert!(parser.is_affected_by(&related).unwrap());
+    let rebuilt = parser.rebuild_incremental(&related).unwrap();
+    assert!(rebuilt.parse("n+n*n"));
+    assert!(!rebuilt.parse("i+i"));
+
+    let absent = GrammarChange::Remove(Production::new(u, vec![Symbol::Terminal('u')]));
+    assert!(parser.rebuild_incremental(&absent).is_err());
+}
+
+#[test]
+fn test_slr1_rebuild_incremental_renumbers_reductions() {
+    // U -> u comes before T and F, so removing it shifts their numbers
+    let lines = vec![
+        "4".to_string(),
+        "S -> S+T T".to_string(),
+        "U -> u".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let change = GrammarChange::Remove(Production::new(
+        Symbol::Nonterminal('U'),
+        vec![Symbol::Terminal('u')],
+    ));
+    assert!(!parser.is_affected_by(&change).unwrap());
+    let reused = parser.rebuild_incremental(&change).unwrap();
+
+    let grammar = reused.grammar().clone();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let fresh = SLR1Parser::build(grammar, follow_sets).unwrap();
+    assert_eq!(reused.action_table(), fresh.action_table());
+
+    assert!(reused.parse("i"));
+    assert!(reused.parse("(i+i)*i"));
+    assert_eq!(reused.coverage(&["i+i"]), fresh.coverage(&["i+i"]));
+}
+
+#[test]
+fn test_slr1_rebuild_incremental_defines_referenced_nonterminal() {
+    // B occurs in a body but has no productions, so it is in no item's
+    // left-hand side
+    let lines = vec!["1".to_string(), "S -> aB".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+    assert!(!parser.parse("ab"));
+
+    let change = GrammarChange::Add(Production::new(
+        Symbol::Nonterminal('B'),
+        vec![Symbol::Terminal('b')],
+    ));
+    assert!(parser.is_affected_by(&change).unwrap());
+    let rebuilt = parser.rebuild_incremental(&change).unwrap();
+    assert!(rebuilt.parse("ab"));
+}
+
+#[test]
+fn test_slr1_explain_conflict() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> L=R R".to_string(),
+        "L -> *R i".to_string(),
+        "R -> L".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let explanation = SLR1Parser::explain_conflict(&grammar, &follow_sets).unwrap();
+    assert!(explanation.starts_with("SLR(1) shift/reduce conflict in state"));
+    assert!(explanation.contains("on lookahead ="));
+    assert!(explanation.contains("shift in S → L•=R because = follows the dot"));
+    assert!(explanation.contains("reduce by R → L• because = is in FOLLOW(R) = {=, $}"));
+
+    let lines = vec![
+        "3".to_string(),
+        "S -> Aa Ba".to_string(),
+        "A -> c".to_string(),
+        "B -> c".to_string(),
+    ];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let explanation = SLR1Parser::explain_conflict(&grammar, &follow_sets).unwrap();
+    assert!(explanation.contains("reduce/reduce conflict"));
+    assert!(explanation.contains("reduce by A → c•"));
+    assert!(explanation.contains("reduce by B → c•"));
+}
+
+#[test]
+fn test_slr1_explain_conflict_tells_dollar_terminal_from_end_marker() {
+    // $ is a terminal here; B is reduced on the end marker only
+    let lines = vec![
+        "3".to_string(),
+        "S -> A$ $ B".to_string(),
+        "A -> e".to_string(),
+        "B -> e".to_string(),
+    ];
+    let config = GrammarConfig::default().with_end_marker('#');
+
+    let grammar = Grammar::parse_with_config(&lines, config).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    assert_eq!(
+        SLR1Parser::explain_conflict(&grammar, &follow_sets).unwrap(),
+        "SLR(1) shift/reduce conflict in state 0 on lookahead $:\n  \
+         shift in S → •$ because $ follows the dot\n  \
+         reduce by A → • because $ is in FOLLOW(A) = {$}\n"
+    );
+}
+
+#[test]
+fn test_slr1_explain_conflict_none_for_slr1_grammar() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    assert_eq!(SLR1Parser::explain_conflict(&grammar, &follow_sets), None);
+}
+
+/// Unwraps the rejection of a failed detailed parse.
+fn rejection_of(result: cfg_parser::error::Result<()>) -> Rejection {
+    match result {
+        Err(GrammarError::Rejected(rejection)) => rejection,
+        other => panic!("expected a rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_slr1_parse_detailed() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert!(parser.parse_detailed("(i+i)*i").is_ok());
+
+    let rejection = rejection_of(parser.parse_detailed("i+*i"));
+    assert_eq!(rejection.position, 2);
+    assert_eq!(rejection.found, Symbol::Terminal('*'));
+    assert_eq!(
+        rejection.expected,
+        vec![Symbol::Terminal('('), Symbol::Terminal('i')]
+    );
+
+    let rejection = rejection_of(parser.parse_detailed("(i"));
+    assert_eq!(rejection.position, 2);
+    assert_eq!(rejection.found, Symbol::EndMarker);
+    assert!(rejection.expected.contains(&Symbol::Terminal(')')));
+    assert!(!rejection.expected.contains(&Symbol::EndMarker));
+}
+
+#[test]
+fn test_slr1_parse_detailed_rejects_nonterminal_input() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let error = parser.parse_detailed("Sa").unwrap_err();
+    assert!(matches!(
+        error,
+        GrammarError::NonterminalInInput { position: 0, .. }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Input may only contain terminals: found S at position 0"
+    );
+}
+
+#[test]
+fn test_slr1_conflict_tags_epsilon_reduce() {
+    // On 'a' the parser can shift for A → a or reduce A → ε
+    let lines = vec![
+        "2".to_string(),
+        "S -> Aa b".to_string(),
+        "A -> e a".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let conflicts = SLR1Parser::conflicts(&grammar, &follow_sets);
+    assert_eq!(conflicts.len(), 1);
+    let GrammarError::SLR1ShiftReduceConflict {
+        symbol,
+        epsilon_reduce,
+        ..
+    } = &conflicts[0]
+    else {
+        panic!("expected a shift/reduce conflict, got {:?}", conflicts[0]);
+    };
+    assert_eq!(symbol, "a");
+    assert!(epsilon_reduce);
+    assert!(conflicts[0]
+        .to_string()
+        .ends_with("(reduce by an ε-production; removing ε-productions may help)"));
+}
+
+#[test]
+fn test_slr1_conflict_without_epsilon_reduce() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> L=R R".to_string(),
+        "L -> *R i".to_string(),
+        "R -> L".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let conflicts = SLR1Parser::conflicts(&grammar, &follow_sets);
+    assert!(matches!(
+        conflicts[0],
+        GrammarError::SLR1ShiftReduceConflict {
+            epsilon_reduce: false,
+            ..
+        }
+    ));
+    assert!(!conflicts[0].to_string().contains("ε"));
+}
+
+#[test]
+fn test_slr1_parse_trimmed() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert!(!parser.parse("i+i "));
+    assert!(parser.parse_trimmed("i+i "));
+    assert!(parser.parse_trimmed("  i+i"));
+    assert!(!parser.parse_trimmed("i + i"));
+}
+
+#[test]
+fn test_slr1_reduce_actions_resolve_to_productions() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar.clone(), follow_sets.clone()).unwrap();
+
+    // Every reduction is on FOLLOW of its production's left-hand side
+    let mut reduced = HashSet::new();
+    for (&(_, lookahead), action) in parser.action_table() {
+        if let Action::Reduce(number) = action {
+            let production = action.production(&grammar).unwrap();
+            assert_eq!(grammar.production_index(production), Some(*number));
+            assert!(follow_sets.follow_of(production.lhs).contains(&lookahead));
+            reduced.insert(*number);
+        }
+    }
+    assert_eq!(reduced, (1..=6).collect());
+
+    for (input, expected) in [
+        ("i", true),
+        ("i+i*i", true),
+        ("(i+i)*i", true),
+        ("i+", false),
+        ("(i", false),
+        ("", false),
+    ] {
+        assert_eq!(parser.parse(input), expected, "input {:?}", input);
+    }
+}
+
+#[test]
+fn test_slr1_unused_reductions() {
+    // C is unreachable from S, so neither of its productions is ever reduced
+    let lines = vec![
+        "3".to_string(),
+        "S -> aB".to_string(),
+        "B -> b".to_string(),
+        "C -> cC d".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert_eq!(
+        parser.unused_reductions(),
+        vec![
+            Production::new(
+                Symbol::Nonterminal('C'),
+                vec![Symbol::Terminal('c'), Symbol::Nonterminal('C')],
+            ),
+            Production::new(Symbol::Nonterminal('C'), vec![Symbol::Terminal('d')]),
+        ]
+    );
+
+    let lines = vec!["1".to_string(), "S -> aS b".to_string()];
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+    assert!(parser.unused_reductions().is_empty());
+}
+
+#[test]
+fn test_slr1_build_verbose_lists_state_items() {
+    // The classic L = R grammar: SLR(1) cannot decide on = after L
+    let lines = vec![
+        "3".to_string(),
+        "S -> L=R R".to_string(),
+        "L -> *R i".to_string(),
+        "R -> L".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+
+    let error = SLR1Parser::build_verbose(grammar.clone(), follow_sets.clone()).unwrap_err();
+    let GrammarError::ConflictInState {
+        conflict,
+        state,
+        items,
+    } = &error
+    else {
+        panic!("expected ConflictInState, got {:?}", error);
+    };
+    assert!(matches!(**conflict, GrammarError::SLR1ShiftReduceConflict { .. }));
+    assert_eq!(items, &["S → L•=R", "R → L•"]);
+
+    let message = error.to_string();
+    let plain = SLR1Parser::build(grammar, follow_sets).unwrap_err();
+    assert!(message.starts_with(&plain.to_string()));
+    assert!(message.contains(&format!(
+        "\nItems of state {}:\n  S → L•=R\n  R → L•",
+        state
+    )));
+}
+
+#[test]
+fn test_slr1_action_sequence() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    // Shift i, then reduce by F → i, T → F and S → T
+    let actions = parser.action_sequence("i").unwrap();
+    assert_eq!(
+        actions,
+        vec![
+            Action::Shift(2),
+            Action::Reduce(6),
+            Action::Reduce(4),
+            Action::Reduce(2),
+            Action::Accept,
+        ]
+    );
+
+    let actions = parser.action_sequence("i+i").unwrap();
+    let rendered: Vec<String> = actions.iter().map(|a| a.to_string()).collect();
+    assert_eq!(rendered.last().unwrap(), "acc");
+    assert_eq!(actions.iter().filter(|a| a.is_shift()).count(), 3);
+
+    assert!(matches!(
+        parser.action_sequence("i+"),
+        Err(GrammarError::Rejected(Rejection { position: 2, .. }))
+    ));
+}
+
+#[test]
+fn test_slr1_parse_with_recovery() {
+    // A bad statement is replaced by the error token up to the next ;
+    let lines = vec![
+        "2".to_string(),
+        "S -> A;S A;".to_string(),
+        "A -> a \\E".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(grammar.terminals().contains(&Symbol::Error));
+    assert_eq!(grammar.to_input_format().unwrap(), "2\nS -> A;S A;\nA -> a \\E\n");
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert_eq!(parser.parse_with_recovery("a;a;").unwrap(), vec![]);
+
+    assert!(!parser.parse("a;bb;a;"));
+    let errors = parser.parse_with_recovery("a;bb;a;").unwrap();
+    assert_eq!(
+        errors,
+        vec![Rejection {
+            position: 2,
+            found: Symbol::Terminal('b'),
+            expected: vec![Symbol::Terminal('a'), Symbol::EndMarker],
+        }]
+    );
+
+    let errors = parser.parse_with_recovery("b;a;;a;").unwrap();
+    let positions: Vec<usize> = errors.iter().map(|e| e.position).collect();
+    assert_eq!(positions, vec![0, 4]);
+
+    // The input ends before the error is synchronized on a ;
+    assert!(matches!(
+        parser.parse_with_recovery("a;b"),
+        Err(GrammarError::Rejected(Rejection {
+            position: 3,
+            found: Symbol::EndMarker,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn test_slr1_parse_with_recovery_without_error_productions() {
+    let lines = vec!["1".to_string(), "S -> aS b".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    assert_eq!(parser.parse_with_recovery("aab").unwrap(), vec![]);
+    assert_eq!(
+        parser.parse_with_recovery("aca").unwrap_err().to_string(),
+        parser.parse_detailed("aca").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_slr1_collection_string() {
+    let lines = vec!["1".to_string(), "S -> aS b".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let parser = SLR1Parser::build(grammar, follow_sets).unwrap();
+
+    let collection = parser.collection_string();
+    assert_eq!(collection.lines().count(), parser.num_states());
+    assert!(collection.starts_with("I0: { S' → •S, S → •aS, S → •b }\n"));
+    for state in 0..parser.num_states() {
+        assert!(collection.contains(&format!("I{}: {{ ", state)));
+    }
+    assert!(collection.contains(": { S → a•S, S → •aS, S → •b }\n"));
+}
@@ -0,0 +1,106 @@
+//! Golden-file tests for the human-facing output of the exporters.
+//!
+//! The expected output lives in `tests/snapshots/`. Run the tests with
+//! `UPDATE_SNAPSHOTS=1` to rewrite the golden files after an intended change.
+
+use cfg_parser::first_follow::{
+    compute_first_sets, compute_follow_sets, format_first_sets, format_follow_sets,
+};
+use cfg_parser::grammar::Grammar;
+use cfg_parser::ll1::LL1Parser;
+use cfg_parser::slr1::SLR1Parser;
+use std::fs;
+use std::path::PathBuf;
+
+/// Compares `actual` with the golden file `tests/snapshots/<name>`.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(name);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected =
+        fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing snapshot {}", path.display()));
+    assert_eq!(actual, expected, "snapshot {} differs", name);
+}
+
+fn example1() -> Grammar {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    Grammar::parse(&lines).unwrap()
+}
+
+fn example2() -> Grammar {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+    Grammar::parse(&lines).unwrap()
+}
+
+fn example3() -> Grammar {
+    let lines = vec![
+        "2".to_string(),
+        "S -> A".to_string(),
+        "A -> A b".to_string(),
+    ];
+    Grammar::parse(&lines).unwrap()
+}
+
+/// Renders the FIRST and FOLLOW sets of a grammar.
+fn first_follow(grammar: &Grammar) -> String {
+    let first_sets = compute_first_sets(grammar);
+    let follow_sets = compute_follow_sets(grammar, &first_sets);
+    format!(
+        "{}{}",
+        format_first_sets(grammar, &first_sets),
+        format_follow_sets(grammar, &follow_sets)
+    )
+}
+
+fn slr1(grammar: Grammar) -> SLR1Parser {
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    SLR1Parser::build(grammar, follow_sets).unwrap()
+}
+
+#[test]
+fn test_snapshot_example1() {
+    let grammar = example1();
+    assert_snapshot("example1_first_follow.txt", &first_follow(&grammar));
+
+    let parser = slr1(grammar);
+    assert_snapshot("example1_slr1.dot", &parser.to_dot());
+    assert_snapshot("example1_slr1.csv", &parser.to_csv());
+}
+
+#[test]
+fn test_snapshot_example2() {
+    let grammar = example2();
+    assert_snapshot("example2_first_follow.txt", &first_follow(&grammar));
+
+    let first_sets = compute_first_sets(&grammar);
+    let follow_sets = compute_follow_sets(&grammar, &first_sets);
+    let ll1 = LL1Parser::build(grammar.clone(), first_sets, follow_sets).unwrap();
+    assert_snapshot("example2_ll1_table.txt", &ll1.table_string());
+
+    let parser = slr1(grammar);
+    assert_snapshot("example2_slr1.dot", &parser.to_dot());
+    assert_snapshot("example2_slr1.csv", &parser.to_csv());
+}
+
+#[test]
+fn test_snapshot_example3() {
+    assert_snapshot("example3_first_follow.txt", &first_follow(&example3()));
+}
@@ -18,6 +18,51 @@ fn test_symbol_ordering() {
     assert!(Symbol::Nonterminal('A') < Symbol::EndMarker);
 }
 
+#[test]
+fn test_symbol_ordering_of_mixed_set() {
+    let mut symbols = vec![
+        Symbol::Nonterminal('S'),
+        Symbol::EndMarker,
+        Symbol::Terminal('i'),
+        Symbol::Terminal('+'),
+        Symbol::Nonterminal('A'),
+        Symbol::Epsilon,
+        Symbol::Terminal('*'),
+        Symbol::Terminal('('),
+    ];
+    symbols.sort();
+    assert_eq!(symbols_to_string(&symbols), "ε(*+iAS$");
+    // Terminals compare by code point, whatever their display width
+    assert!(Symbol::Terminal('9') < Symbol::Terminal('a'));
+    assert!(Symbol::Terminal('z') < Symbol::Terminal('数'));
+}
+
+#[test]
+fn test_special_symbols() {
+    let augmented = Symbol::Special("S'");
+    let root = Symbol::Special("root");
+
+    // Special markers sort after every grammar symbol, then by label
+    assert!(Symbol::EndMarker < augmented);
+    assert!(Symbol::Nonterminal('Z') < augmented);
+    assert!(augmented < root);
+    let mut symbols = vec![root, Symbol::EndMarker, augmented, Symbol::Terminal('a')];
+    symbols.sort();
+    assert_eq!(
+        symbols,
+        vec![Symbol::Terminal('a'), Symbol::EndMarker, augmented, root]
+    );
+
+    // They never equal a grammar symbol, even one printed the same way
+    assert_ne!(Symbol::Special("'"), Symbol::Nonterminal('\''));
+    assert_ne!(Symbol::Special("$"), Symbol::EndMarker);
+    assert!(augmented.is_special());
+    assert!(!augmented.is_nonterminal() && !augmented.is_terminal());
+    assert_eq!(augmented.as_char(), None);
+    assert_eq!(augmented.to_string(), "S'");
+    assert_eq!(augmented.display_width(), 2);
+}
+
 #[test]
 fn test_string_conversion() {
     let symbols = string_to_symbols("AaB");
@@ -52,3 +97,54 @@ fn test_epsilon_special_case() {
     assert!(!epsilon.is_terminal());
     assert!(!epsilon.is_nonterminal());
 }
+
+#[test]
+fn test_explicit_constructors_override_casing() {
+    assert!(Symbol::terminal('A').is_terminal());
+    assert!(Symbol::nonterminal('a').is_nonterminal());
+    assert_eq!(Symbol::terminal('A'), Symbol::Terminal('A'));
+    assert_ne!(Symbol::terminal('A'), Symbol::from_char('A'));
+    assert_eq!(Symbol::nonterminal('S'), Symbol::from_char('S'));
+}
+
+#[test]
+fn test_display_width() {
+    assert_eq!(Symbol::Terminal('a').display_width(), 1);
+    assert_eq!(Symbol::Epsilon.display_width(), 1);
+    assert_eq!(Symbol::EndMarker.display_width(), 1);
+    assert_eq!(Symbol::Error.display_width(), 5);
+    assert_eq!(display_width("aε$"), 3);
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("a\\nb"), "a\nb");
+    assert_eq!(unescape("\\t"), "\t");
+    assert_eq!(unescape("x\\sy"), "x y");
+    assert_eq!(unescape("\\\\n"), "\\n");
+    // Unknown escapes and a trailing backslash are kept
+    assert_eq!(unescape("\\q\\"), "\\q\\");
+}
+
+#[test]
+fn test_escaped_terminal_display() {
+    for (c, escaped) in [('\n', "\\n"), ('\t', "\\t"), (' ', "\\s"), ('\\', "\\\\")] {
+        let symbol = Symbol::Terminal(c);
+        assert_eq!(symbol.to_string(), escaped);
+        assert_eq!(symbol.display_width(), 2);
+        assert_eq!(string_to_symbols(&unescape(escaped)), vec![symbol]);
+    }
+    assert_eq!(
+        symbols_to_string(&[Symbol::Terminal('a'), Symbol::Terminal(' ')]),
+        "a\\s"
+    );
+}
+
+#[test]
+fn test_error_symbol() {
+    assert!(Symbol::Error.is_terminal());
+    assert_eq!(Symbol::Error.as_char(), None);
+    assert_eq!(Symbol::Error.to_string(), "error");
+    assert!(Symbol::Terminal('z') < Symbol::Error);
+    assert!(Symbol::Error < Symbol::Nonterminal('A'));
+}
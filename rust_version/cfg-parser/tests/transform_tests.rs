@@ -0,0 +1,339 @@
+//! Unit tests for grammar transformations
+
+use cfg_parser::earley::EarleyParser;
+use cfg_parser::first_follow::{compute_first_sets, compute_follow_sets};
+use cfg_parser::grammar::{languages_equal_modulo_empty, Grammar, GrammarConfig, Production};
+use cfg_parser::ll1::LL1Parser;
+use cfg_parser::symbol::Symbol;
+use cfg_parser::transform::*;
+
+fn expression_grammar() -> Grammar {
+    let lines = vec![
+        "3".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> T*F F".to_string(),
+        "F -> (S) i".to_string(),
+    ];
+    Grammar::parse(&lines).unwrap()
+}
+
+fn is_ll1(grammar: &Grammar) -> bool {
+    let first_sets = compute_first_sets(grammar);
+    let follow_sets = compute_follow_sets(grammar, &first_sets);
+    LL1Parser::build(grammar.clone(), first_sets, follow_sets).is_ok()
+}
+
+fn has_direct_left_recursion(grammar: &Grammar) -> bool {
+    grammar
+        .all_productions()
+        .iter()
+        .any(|p| p.rhs.first() == Some(&p.lhs))
+}
+
+/// Checks that both grammars agree on every sample input.
+fn assert_same_language(a: &Grammar, b: &Grammar, samples: &[&str]) {
+    let parser_a = EarleyParser::new(a.clone());
+    let parser_b = EarleyParser::new(b.clone());
+    for sample in samples {
+        assert_eq!(
+            parser_a.parse(sample),
+            parser_b.parse(sample),
+            "grammars disagree on '{}'",
+            sample
+        );
+    }
+}
+
+const EXPRESSION_SAMPLES: &[&str] = &[
+    "i", "i+i", "i*i", "(i)", "i+i*i", "(i+i)*i", "((i))", "", "+", "i+", "(i", "ii", "i)",
+];
+
+#[test]
+fn test_remove_useless_symbols() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> a A".to_string(),
+        "A -> Ab".to_string(),
+        "B -> c".to_string(),
+    ];
+
+    // A is unproductive, B is unreachable
+    let grammar = Grammar::parse(&lines).unwrap();
+    let reduced = remove_useless_symbols(&grammar).unwrap();
+
+    assert_eq!(reduced.all_productions().len(), 1);
+    assert_eq!(reduced.all_productions()[0].to_string(), "S → a");
+}
+
+#[test]
+fn test_remove_epsilon_productions() {
+    let lines = vec![
+        "3".to_string(),
+        "S -> AB".to_string(),
+        "A -> aA d".to_string(),
+        "B -> bBc e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let result = remove_epsilon_productions(&grammar).unwrap();
+
+    assert!(result.all_productions().iter().all(|p| !p.is_epsilon()));
+    assert!(result.all_productions().contains(&Production::new(
+        Symbol::Nonterminal('B'),
+        vec![Symbol::Terminal('b'), Symbol::Terminal('c')],
+    )));
+    assert_same_language(
+        &grammar,
+        &result,
+        &["d", "ad", "dbc", "adbbcc", "a", "dbcc"],
+    );
+}
+
+#[test]
+fn test_remove_epsilon_productions_preserves_nonempty_language() {
+    let lines = vec!["1".to_string(), "S -> aSb e".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let result = remove_epsilon_productions(&grammar).unwrap();
+
+    // Only ε is lost: S -> aSb | ab
+    assert!(grammar.accepts("").unwrap());
+    assert!(!result.accepts("").unwrap());
+    assert!(languages_equal_modulo_empty(&grammar, &result, 6));
+
+    let lines = vec!["1".to_string(), "S -> aSb ab b".to_string()];
+    let different = Grammar::parse(&lines).unwrap();
+    assert!(!languages_equal_modulo_empty(&grammar, &different, 6));
+}
+
+#[test]
+fn test_remove_unit_productions() {
+    let grammar = expression_grammar();
+    let result = remove_unit_productions(&grammar).unwrap();
+
+    assert!(result.all_productions().iter().all(|p| !p.is_unit()));
+    assert_same_language(&grammar, &result, EXPRESSION_SAMPLES);
+}
+
+#[test]
+fn test_eliminate_left_recursion() {
+    let grammar = expression_grammar();
+    let result = eliminate_left_recursion(&grammar).unwrap();
+
+    assert!(!has_direct_left_recursion(&result));
+    assert!(is_ll1(&result));
+    assert_same_language(&grammar, &result, EXPRESSION_SAMPLES);
+}
+
+#[test]
+fn test_left_factor() {
+    let lines = vec!["1".to_string(), "S -> abc abd b".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(!is_ll1(&grammar));
+
+    let result = left_factor(&grammar).unwrap();
+    assert_eq!(result.get_productions(Symbol::Nonterminal('S')).len(), 2);
+    assert!(is_ll1(&result));
+    assert_same_language(&grammar, &result, &["abc", "abd", "b", "ab", "abcd"]);
+}
+
+#[test]
+fn test_right_factor() {
+    let grammar = Grammar::parse(&["1".to_string(), "S -> xc yc b".to_string()]).unwrap();
+
+    let result = right_factor(&grammar).unwrap();
+    let rendered: Vec<String> = result
+        .all_productions()
+        .iter()
+        .map(Production::to_string)
+        .collect();
+    assert_eq!(rendered, ["S → Ac", "S → b", "A → x", "A → y"]);
+    assert!(languages_equal_modulo_empty(&grammar, &result, 4));
+
+    // The longest common suffix is factored, then the remaining heads again
+    let nested = Grammar::parse(&["1".to_string(), "S -> xbc ybc c".to_string()]).unwrap();
+    let result = GrammarPipeline::new(nested.clone())
+        .right_factor()
+        .build()
+        .unwrap();
+    assert_eq!(result.get_productions(Symbol::Nonterminal('S')).len(), 1);
+    assert!(languages_equal_modulo_empty(&nested, &result, 5));
+}
+
+#[test]
+fn test_transforms_preserve_start_symbol() {
+    let grammar = expression_grammar();
+    let result = eliminate_left_recursion(&grammar).unwrap();
+    assert_eq!(result.start_symbol(), grammar.start_symbol());
+}
+
+#[test]
+fn test_name_gen_avoids_existing_nonterminals() {
+    let grammar = expression_grammar();
+    let mut names = NameGen::new(&grammar);
+
+    let first = names.fresh().unwrap();
+    let second = names.fresh().unwrap();
+    assert_ne!(first, second);
+    assert!(!grammar.nonterminals().contains(&first));
+    assert!(!grammar.nonterminals().contains(&second));
+}
+
+#[test]
+fn test_pipeline_on_expression_grammar() {
+    let grammar = expression_grammar();
+    let result = GrammarPipeline::new(grammar.clone())
+        .remove_useless()
+        .remove_epsilon()
+        .remove_unit()
+        .eliminate_left_recursion()
+        .left_factor()
+        .build()
+        .unwrap();
+
+    assert!(!has_direct_left_recursion(&result));
+    assert!(result.all_productions().iter().all(|p| !p.is_unit()));
+    assert_same_language(&grammar, &result, EXPRESSION_SAMPLES);
+
+    let first_sets = compute_first_sets(&result);
+    let follow_sets = compute_follow_sets(&result, &first_sets);
+    let parser = LL1Parser::build(result, first_sets, follow_sets);
+    assert!(parser.is_ok());
+    assert!(parser.unwrap().parse("i+i*i"));
+}
+
+#[test]
+fn test_pipeline_produces_ll1_grammar() {
+    // Left-recursive sums over identifiers and calls: not LL(1) as written
+    let lines = vec![
+        "2".to_string(),
+        "S -> S+T T".to_string(),
+        "T -> i i(S)".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(!is_ll1(&grammar));
+
+    let result = GrammarPipeline::new(grammar.clone())
+        .remove_useless()
+        .remove_epsilon()
+        .remove_unit()
+        .eliminate_left_recursion()
+        .left_factor()
+        .build()
+        .unwrap();
+
+    assert!(is_ll1(&result));
+    assert_same_language(
+        &grammar,
+        &result,
+        &["i", "i+i", "i(i)", "i(i+i)+i", "i(", "+i", ""],
+    );
+}
+
+#[test]
+fn test_pipeline_reports_errors() {
+    let lines = vec!["2".to_string(), "S -> A".to_string(), "A -> Ab".to_string()];
+
+    // Every symbol is useless, so no productions remain
+    let grammar = Grammar::parse(&lines).unwrap();
+    assert!(GrammarPipeline::new(grammar)
+        .remove_useless()
+        .left_factor()
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_to_cnf() {
+    let grammar = expression_grammar();
+    let result = to_cnf(&grammar).unwrap();
+
+    assert!(result.is_cnf());
+    assert_eq!(result.start_symbol(), grammar.start_symbol());
+    assert_same_language(&grammar, &result, EXPRESSION_SAMPLES);
+}
+
+#[test]
+fn test_to_cnf_keeps_empty_string() {
+    // S occurs on a right-hand side, so ε needs a new start symbol
+    let lines = vec!["1".to_string(), "S -> aSbS bSaS e".to_string()];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let result = to_cnf(&grammar).unwrap();
+    assert!(result.is_cnf());
+    assert_ne!(result.start_symbol(), grammar.start_symbol());
+    assert_same_language(
+        &grammar,
+        &result,
+        &["", "ab", "ba", "abba", "baab", "aabb", "a", "aab", "bb"],
+    );
+}
+
+#[test]
+fn test_to_cnf_and_derivative_keep_config() {
+    // A new start symbol is needed, and $ is an ordinary terminal
+    let lines = vec!["1".to_string(), "S -> aSbS $ e".to_string()];
+    let config = GrammarConfig::default().with_end_marker('#');
+    let grammar = Grammar::parse_with_config(&lines, config.clone()).unwrap();
+
+    let cnf = to_cnf(&grammar).unwrap();
+    assert_ne!(cnf.start_symbol(), grammar.start_symbol());
+    assert_eq!(cnf.config(), &config);
+    assert_eq!(cnf.tokenize("ab$").unwrap()[2], Symbol::Terminal('$'));
+
+    let derivative = grammar.derivative(Symbol::Terminal('a')).unwrap();
+    assert_eq!(derivative.config(), &config);
+}
+
+#[test]
+fn test_to_gnf() {
+    let grammar = expression_grammar();
+    let result = to_gnf(&grammar).unwrap();
+
+    assert!(result.is_gnf());
+    assert!(!has_direct_left_recursion(&result));
+    assert_same_language(&grammar, &result, EXPRESSION_SAMPLES);
+}
+
+#[test]
+fn test_to_gnf_with_indirect_left_recursion() {
+    let lines = vec![
+        "2".to_string(),
+        "S -> Ab".to_string(),
+        "A -> Sa b e".to_string(),
+    ];
+
+    let grammar = Grammar::parse(&lines).unwrap();
+    let result = GrammarPipeline::new(grammar.clone()).to_gnf().build().unwrap();
+    assert!(result.is_gnf());
+    assert_same_language(
+        &grammar,
+        &result,
+        &["b", "bb", "bab", "bbab", "babab", "", "a", "ba", "abb"],
+    );
+}
+
+#[test]
+fn test_transforms_preserve_non_s_start_symbol() {
+    let e = Symbol::Nonterminal('E');
+    let t = Symbol::Nonterminal('T');
+    let productions = vec![
+        Production::new(e, vec![e, Symbol::Terminal('+'), t]),
+        Production::new(e, vec![t]),
+        Production::new(t, vec![Symbol::Terminal('i')]),
+    ];
+    let grammar = Grammar::from_productions_with_start(productions, e).unwrap();
+
+    let result = GrammarPipeline::new(grammar.clone())
+        .remove_useless()
+        .remove_unit()
+        .eliminate_left_recursion()
+        .left_factor()
+        .to_cnf()
+        .build()
+        .unwrap();
+    assert_eq!(result.start_symbol(), e);
+    assert_same_language(&grammar, &result, &["i", "i+i", "i+i+i", "", "+", "i+"]);
+}